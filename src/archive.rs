@@ -0,0 +1,167 @@
+// Packed multi-entry archive format: a fixed magic + version, a name-sorted directory (each
+// entry's name, byte range, and content hash), then the entries' bytes themselves, 8-byte
+// aligned so entries can be read back (or mmap'd) without touching their neighbours.
+//
+// Used to bundle one or more `PageSerializer`s' raw page streams into a single file -- see
+// `PageSerializer::export_archive`/`open_archive`.
+
+use std::io::{Read, Seek, SeekFrom, Write};
+
+use crate::hash::hash;
+
+const ARCHIVE_MAGIC: u64 = 0x3152_4143_3144_4231; // "1BD1CAR1", just a fixed recognizable tag
+const ARCHIVE_VERSION: u8 = 1;
+const ARCHIVE_ALIGN: u64 = 8;
+
+#[derive(Debug, Clone)]
+struct DirEntry {
+    name: String,
+    offset: u64,
+    len: u64,
+    hash: u64,
+}
+
+fn align_up(x: u64, align: u64) -> u64 {
+    (x + align - 1) / align * align
+}
+
+fn write_zeros<W: Write>(w: &mut W, n: u64) {
+    if n > 0 {
+        w.write_all(&vec![0u8; n as usize]).unwrap();
+    }
+}
+
+// Writes `entries` (name -> raw bytes) into `w` as one archive. Entries are stored in the
+// directory sorted by name so lookups can binary search it.
+pub fn write_archive<W: Write + Seek>(w: &mut W, entries: &[(&str, &[u8])]) {
+    let mut sorted: Vec<(&str, &[u8])> = entries.to_vec();
+    sorted.sort_by_key(|(name, _)| *name);
+
+    let dir_header_len: u64 = 8 + 1 + 4;
+    let dir_body_len: u64 = sorted
+        .iter()
+        .map(|(name, _)| 4 + name.len() as u64 + 8 + 8 + 8)
+        .sum();
+    let payload_start = align_up(dir_header_len + dir_body_len, ARCHIVE_ALIGN);
+
+    let mut dirs = Vec::with_capacity(sorted.len());
+    let mut cursor = payload_start;
+    for (name, bytes) in &sorted {
+        let offset = cursor;
+        let len = bytes.len() as u64;
+        dirs.push(DirEntry {
+            name: name.to_string(),
+            offset,
+            len,
+            hash: hash(&bytes.to_vec()),
+        });
+        cursor = align_up(offset + len, ARCHIVE_ALIGN);
+    }
+
+    w.write_all(&ARCHIVE_MAGIC.to_le_bytes()).unwrap();
+    w.write_all(&[ARCHIVE_VERSION]).unwrap();
+    w.write_all(&(dirs.len() as u32).to_le_bytes()).unwrap();
+    for d in &dirs {
+        w.write_all(&(d.name.len() as u32).to_le_bytes()).unwrap();
+        w.write_all(d.name.as_bytes()).unwrap();
+        w.write_all(&d.offset.to_le_bytes()).unwrap();
+        w.write_all(&d.len.to_le_bytes()).unwrap();
+        w.write_all(&d.hash.to_le_bytes()).unwrap();
+    }
+
+    let pos = w.stream_position().unwrap();
+    write_zeros(w, payload_start - pos);
+
+    for ((_, bytes), d) in sorted.iter().zip(&dirs) {
+        debug_assert_eq!(w.stream_position().unwrap(), d.offset);
+        w.write_all(bytes).unwrap();
+        let pos = w.stream_position().unwrap();
+        write_zeros(w, align_up(pos, ARCHIVE_ALIGN) - pos);
+    }
+}
+
+// Reads an archive's directory up front; individual entries are only read (and hash-checked)
+// on demand via `read_entry`, so opening a large archive to pull out one small table is cheap.
+pub struct ArchiveReader<R> {
+    r: R,
+    dirs: Vec<DirEntry>,
+}
+
+impl<R: Read + Seek> ArchiveReader<R> {
+    pub fn open(mut r: R) -> Self {
+        r.seek(SeekFrom::Start(0)).unwrap();
+
+        let mut magic_buf = [0u8; 8];
+        r.read_exact(&mut magic_buf).unwrap();
+        assert_eq!(u64::from_le_bytes(magic_buf), ARCHIVE_MAGIC, "not a db1 archive");
+
+        let mut version_buf = [0u8; 1];
+        r.read_exact(&mut version_buf).unwrap();
+        assert_eq!(version_buf[0], ARCHIVE_VERSION, "unsupported archive version");
+
+        let mut count_buf = [0u8; 4];
+        r.read_exact(&mut count_buf).unwrap();
+        let count = u32::from_le_bytes(count_buf);
+
+        let mut dirs = Vec::with_capacity(count as usize);
+        for _ in 0..count {
+            let mut name_len_buf = [0u8; 4];
+            r.read_exact(&mut name_len_buf).unwrap();
+            let mut name = vec![0u8; u32::from_le_bytes(name_len_buf) as usize];
+            r.read_exact(&mut name).unwrap();
+
+            let mut offset_buf = [0u8; 8];
+            r.read_exact(&mut offset_buf).unwrap();
+            let mut len_buf = [0u8; 8];
+            r.read_exact(&mut len_buf).unwrap();
+            let mut hash_buf = [0u8; 8];
+            r.read_exact(&mut hash_buf).unwrap();
+
+            dirs.push(DirEntry {
+                name: String::from_utf8(name).unwrap(),
+                offset: u64::from_le_bytes(offset_buf),
+                len: u64::from_le_bytes(len_buf),
+                hash: u64::from_le_bytes(hash_buf),
+            });
+        }
+
+        ArchiveReader { r, dirs }
+    }
+
+    pub fn entry_names(&self) -> Vec<&str> {
+        self.dirs.iter().map(|d| d.name.as_str()).collect()
+    }
+
+    // Binary searches the directory by name and reads that entry's bytes back, panicking if
+    // its content hash no longer matches (the entry was truncated or corrupted).
+    pub fn read_entry(&mut self, name: &str) -> Option<Vec<u8>> {
+        let idx = self
+            .dirs
+            .binary_search_by(|d| d.name.as_str().cmp(name))
+            .ok()?;
+        let d = self.dirs[idx].clone();
+
+        self.r.seek(SeekFrom::Start(d.offset)).unwrap();
+        let mut buf = vec![0u8; d.len as usize];
+        self.r.read_exact(&mut buf).unwrap();
+        assert_eq!(hash(&buf), d.hash, "archive entry {:?} failed its content-hash check", name);
+        Some(buf)
+    }
+}
+
+#[test]
+fn test_archive_roundtrip() {
+    use std::io::Cursor;
+
+    let a = b"hello world".to_vec();
+    let b = b"a much longer second entry, to exercise alignment padding".to_vec();
+
+    let mut archive = Cursor::new(Vec::new());
+    write_archive(&mut archive, &[("b", &b), ("a", &a)]);
+
+    let mut reader = ArchiveReader::open(Cursor::new(archive.into_inner()));
+    assert_eq!(reader.entry_names(), vec!["a", "b"]);
+    assert_eq!(reader.read_entry("a"), Some(a));
+    assert_eq!(reader.read_entry("b"), Some(b));
+    assert_eq!(reader.read_entry("missing"), None);
+}
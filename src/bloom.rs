@@ -0,0 +1,119 @@
+// A small, self-contained Bloom filter used to let chunk scans skip whole pages whose
+// filter proves a token is absent (e.g. `ImageDocument::filename`/`description` words).
+// Hashing uses two independent 64-bit FNV-1a passes combined into a 128-bit hash (the crate
+// has no xxhash dependency to pull in xxh3_128, so this plays the same "one wide hash, double
+// hashing for k bit positions" role).
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct BloomFilter {
+    m: u64,
+    k: u8,
+    bits: Vec<u8>,
+}
+
+fn fnv1a64(data: &[u8], seed: u64) -> u64 {
+    let mut hash = 0xcbf29ce484222325u64 ^ seed;
+    for &b in data {
+        hash ^= b as u64;
+        hash = hash.wrapping_mul(0x100000001b3);
+    }
+    hash
+}
+
+fn hash128(token: &[u8]) -> u128 {
+    let h1 = fnv1a64(token, 0xcbf29ce484222325);
+    let h2 = fnv1a64(token, 0x9e3779b97f4a7c15);
+    ((h1 as u128) << 64) | (h2 as u128)
+}
+
+impl BloomFilter {
+    pub const K: u8 = 7;
+
+    // Size the bit array from the expected number of distinct tokens: m = ceil(1.44 * k * n).
+    pub fn new_for_token_count(n: usize) -> Self {
+        let m = ((1.44 * Self::K as f64 * n.max(1) as f64).ceil() as u64).max(8);
+        Self {
+            m,
+            k: Self::K,
+            bits: vec![0u8; ((m + 7) / 8) as usize],
+        }
+    }
+
+    pub const DEFAULT_BITS_PER_KEY: u32 = 10;
+
+    // LevelDB-style sizing for a fixed-cardinality key set (e.g. one page's primary keys):
+    // `m = n * bits_per_key` bits, `k` chosen so the false-positive rate is near-minimal for
+    // that many bits per key (k ~= bits_per_key * ln(2)). Reuses the same bit array/double-hash
+    // machinery as `new_for_token_count` -- only the sizing formula differs, since m and k are
+    // already per-instance rather than fixed constants.
+    pub fn new_for_key_count(n: usize, bits_per_key: u32) -> Self {
+        let m = (n.max(1) as u64 * bits_per_key as u64).max(8);
+        let k = ((bits_per_key as f64 * std::f64::consts::LN_2).round() as u8).max(1);
+        Self {
+            m,
+            k,
+            bits: vec![0u8; ((m + 7) / 8) as usize],
+        }
+    }
+
+    pub fn empty() -> Self {
+        Self { m: 0, k: 0, bits: Vec::new() }
+    }
+
+    fn bit_positions(&self, token: &[u8]) -> impl Iterator<Item = u64> + '_ {
+        let h = hash128(token);
+        let h1 = h as u64;
+        let h2 = (h >> 64) as u64;
+        let m = self.m;
+        (0..self.k as u64).map(move |i| h1.wrapping_add(i.wrapping_mul(h2)) % m)
+    }
+
+    fn set_bit(&mut self, i: u64) {
+        self.bits[(i / 8) as usize] |= 1 << (i % 8);
+    }
+
+    fn get_bit(&self, i: u64) -> bool {
+        self.bits[(i / 8) as usize] & (1 << (i % 8)) != 0
+    }
+
+    pub fn insert(&mut self, token: &[u8]) {
+        if self.m == 0 {
+            return;
+        }
+        for i in self.bit_positions(token).collect::<Vec<_>>() {
+            self.set_bit(i);
+        }
+    }
+
+    // False positives are possible; false negatives are not. An empty filter (m == 0, e.g.
+    // one read back from a pre-Bloom-filter file) always reports "might contain" so callers
+    // fall back to an exact scan instead of skipping.
+    pub fn might_contain(&self, token: &[u8]) -> bool {
+        if self.m == 0 {
+            return true;
+        }
+        self.bit_positions(token).all(|i| self.get_bit(i))
+    }
+
+    pub fn m(&self) -> u64 {
+        self.m
+    }
+    pub fn k(&self) -> u8 {
+        self.k
+    }
+    pub fn bits(&self) -> &[u8] {
+        &self.bits
+    }
+    pub fn from_parts(m: u64, k: u8, bits: Vec<u8>) -> Self {
+        Self { m, k, bits }
+    }
+}
+
+// Lowercased, whitespace/punctuation-split tokens, matching what gets fed into the filter
+// at flush time and what queries hash when probing it.
+pub fn tokenize(s: &str) -> Vec<String> {
+    s.split(|c: char| !c.is_alphanumeric())
+        .filter(|t| !t.is_empty())
+        .map(|t| t.to_lowercase())
+        .collect()
+}
@@ -28,27 +28,47 @@ use crate::db1_string::Db1String;
 pub use crate::{
     bytes_serializer::BytesSerialize, bytes_serializer::FromReader, chunk_header::ChunkHeader,
     suitable_data_type::DataType, suitable_data_type::SuitableDataType,
+    table_manager::TableManager, typed_row::Predicate, typed_row::TypedRow,
 };
 
+mod archive;
+mod bloom;
 mod buffer_pool;
 mod bytes_serializer;
 mod chunk_header;
 mod compressor;
 mod db1_string;
+mod dictionary;
 mod dynamic_tuple;
+mod external_sort;
+mod free_list;
 mod hash;
 mod heap_writer;
 mod index;
+mod layout;
+mod lockfree_pool;
+mod lru;
+mod mmap_storage;
+mod parser;
+mod pg_server;
 mod query_data;
 mod range;
+mod read_at;
 mod serializer;
 mod suitable_data_type;
 mod table_base;
 mod table_base2;
+mod table_cursor;
 mod table_manager;
 mod table_traits;
+mod text_format;
+mod text_index;
 mod tests;
 mod ra_ops;
 mod secondary_index;
+mod transaction;
 mod typed_table;
 mod named_tables;
+mod type_data;
+mod typed_row;
+mod wal;
@@ -8,16 +8,29 @@ use table_base2::TableType;
 
 
 
+use crate::bloom::BloomFilter;
 use crate::bytes_serializer::{BytesSerialize, FromReader};
 use crate::range::Range;
 
 const CH_CHECK_SEQUENCE: u64 = 0x32aa842f80ad9;
 
+// Bumped when the on-disk header format gains fields. Readers that see an older version
+// just get the defaults for whatever was added since: an empty Bloom filter (version 1,
+// which always reports "might contain" and falls back to an exact scan), codec `None`
+// (version 2, i.e. the page body is stored as-is), an empty primary-key Bloom filter
+// (version 3, same "might contain" fallback as the version-1 token filter),
+// `restart_encoded = false` (version 4, i.e. the page body is the plain fixed-width layout),
+// `key_delta_encoded = false` (version 5, i.e. the key column is stored as raw u64s
+// rather than delta+varint encoded), and an empty `column_zonemaps` (version 6, which makes
+// `PageSerializer::get_in_all_by_zonemap` treat every page as a candidate instead of pruning).
+const HEADER_VERSION: u8 = 6;
+
 impl BytesSerialize for ChunkHeader {
     fn serialize_with_heap<W: Write, W1: Write + Seek>(&self, mut w: W, mut _heap: W1) {
         // w.write_all(&CH_CHECK_SEQUENCE.to_le_bytes()).unwrap();
         let mut rc = ReadContainer {
             check_sequence: CH_CHECK_SEQUENCE,
+            version: HEADER_VERSION,
             ty: self.ty,
             tot_len: self.tot_len,
             type_size: self.type_size,
@@ -25,13 +38,36 @@ impl BytesSerialize for ChunkHeader {
             heap_size: self.heap_size,
             compressed_size: self.compressed_size,
             table_type: self.table_type.to_u8(),
+            codec: self.codec,
+            restart_encoded: self.restart_encoded as u8,
+            key_delta_encoded: self.key_delta_encoded as u8,
         };
         w.write_all(slice_from_type(&mut rc)).unwrap();
 
         let mut heap: Cursor<Vec<u8>> = Cursor::default();
         self.limits.serialize_with_heap(&mut w, &mut heap);
+
+        // Zone maps: one (min, max) `Range<TypeData>` per column, so `get_in_all_by_zonemap`
+        // can skip a page for an equality filter on any column, not just the primary key.
+        // Appended to the same heap-backed stream `limits` uses above, since only string-typed
+        // columns actually need heap bytes.
+        w.write_all(&(self.column_zonemaps.len() as u32).to_le_bytes()).unwrap();
+        for zonemap in &self.column_zonemaps {
+            zonemap.serialize_with_heap(&mut w, &mut heap);
+        }
+
         w.write_all(&heap.stream_len().unwrap().to_le_bytes());
         w.write_all(heap.get_ref().as_slice());
+
+        w.write_all(&self.bloom.m().to_le_bytes()).unwrap();
+        w.write_all(&self.bloom.k().to_le_bytes()).unwrap();
+        w.write_all(&(self.bloom.bits().len() as u32).to_le_bytes()).unwrap();
+        w.write_all(self.bloom.bits()).unwrap();
+
+        w.write_all(&self.pkey_bloom.m().to_le_bytes()).unwrap();
+        w.write_all(&self.pkey_bloom.k().to_le_bytes()).unwrap();
+        w.write_all(&(self.pkey_bloom.bits().len() as u32).to_le_bytes()).unwrap();
+        w.write_all(self.pkey_bloom.bits()).unwrap();
     }
 }
 
@@ -45,15 +81,48 @@ pub struct ChunkHeader {
     pub type_size: u32,
     pub tuple_count: u32,
     pub heap_size: u32,
+    // Kept as a materialized `Range<TypeData>` rather than raw memcmp bytes -- it's read back by
+    // callers that need the real value (e.g. `TableBase2::split`'s exact-stats recompute,
+    // `get_in_all_by_zonemap`'s per-column overlap checks), not just ordered against other
+    // bounds. `MinKey` below is the one place that only ever needs ordering, so it stores
+    // `TypeData::encode_memcmp()` bytes directly instead.
     pub limits: Range<TypeData>,
     pub compressed_size: u32,
     pub table_type: TableType,
+    // Per-chunk Bloom filter over tokens seen in this chunk's string fields, so point/token
+    // lookups can skip the chunk entirely without decoding it. Empty on chunks written
+    // before `HEADER_VERSION` 1 or on chunks with no string columns.
+    pub bloom: BloomFilter,
+    // Which codec (if any) the page body was compressed with; see `compressor::Codec`.
+    // `compressed_size` carries the on-disk (compressed) length, `tot_len` the original one.
+    pub codec: u8,
+    // Per-chunk Bloom filter over this chunk's primary keys (`TypeData::encode_memcmp()`
+    // bytes), so `TableBase2::search_value` can reject an absent key without a binary search.
+    // Empty on chunks written before `HEADER_VERSION` 3.
+    pub pkey_bloom: BloomFilter,
+    // Whether the page body's row data is the LevelDB-style restart-point prefix-compressed
+    // key stream (`TableBase2::encode_restart_keys`) rather than the plain fixed-width row
+    // array. Only ever set for `TableType::Index(Type::String)` pages. Always `false` on
+    // chunks written before `HEADER_VERSION` 4.
+    pub restart_encoded: bool,
+    // Whether the page body's primary-key column is delta+varint encoded
+    // (`TableBase2::encode_delta_keys`) rather than stored as raw little-endian `u64`s. Only
+    // ever set for `TableType::Data`/`Index(Type::Int)` pages. Always `false` on chunks
+    // written before `HEADER_VERSION` 5.
+    pub key_delta_encoded: bool,
+    // Per-column (min, max) stats computed when this page was last flushed/split, indexed by
+    // column position -- lets `PageSerializer::get_in_all_by_zonemap` skip a page for an
+    // equality filter on any column, the same way `limits` already does for the primary key.
+    // Empty whenever no stats were computed for this page (including every chunk written
+    // before `HEADER_VERSION` 6), which callers must treat as "no stats, don't skip".
+    pub column_zonemaps: Vec<Range<TypeData>>,
 }
 
 #[derive(Default, Debug)]
 #[repr(C)]
 struct ReadContainer {
     check_sequence: u64,
+    version: u8,
     ty: u64,
     tot_len: u32,
     type_size: u32,
@@ -61,6 +130,9 @@ struct ReadContainer {
     heap_size: u32,
     compressed_size: u32,
     table_type: u8,
+    codec: u8,
+    restart_encoded: u8,
+    key_delta_encoded: u8,
 }
 
 pub fn slice_from_type<T: Sized>(t: &mut T) -> &mut [u8] {
@@ -79,6 +151,17 @@ impl FromReader for Option<ChunkHeader> {
         }
         let mut limits = Range::from_reader_and_heap(&mut r, &[]);
 
+        let column_zonemap_count = if rc.version >= 6 {
+            let mut n = 0u32;
+            r.read_exact(slice_from_type(&mut n)).unwrap();
+            n
+        } else {
+            0
+        };
+        let mut column_zonemaps: Vec<Range<TypeData>> = (0..column_zonemap_count)
+            .map(|_| Range::from_reader_and_heap(&mut r, &[]))
+            .collect();
+
         let mut ch_heap_len = 0u64;
         r.read_exact(slice_from_type(&mut ch_heap_len)).unwrap();
         let mut ch_heap = Vec::default();
@@ -86,6 +169,32 @@ impl FromReader for Option<ChunkHeader> {
         r.read_exact(&mut ch_heap);
 
         limits.resolve(&ch_heap);
+        for zonemap in &mut column_zonemaps {
+            zonemap.resolve(&ch_heap);
+        }
+
+        let read_bloom = |r: &mut R| {
+            let mut m = 0u64;
+            let mut k = 0u8;
+            let mut bits_len = 0u32;
+            r.read_exact(slice_from_type(&mut m)).unwrap();
+            r.read_exact(slice_from_type(&mut k)).unwrap();
+            r.read_exact(slice_from_type(&mut bits_len)).unwrap();
+            let mut bits = vec![0u8; bits_len as usize];
+            r.read_exact(&mut bits).unwrap();
+            BloomFilter::from_parts(m, k, bits)
+        };
+
+        let bloom = if rc.version >= 1 {
+            read_bloom(&mut r)
+        } else {
+            BloomFilter::empty()
+        };
+        let pkey_bloom = if rc.version >= 3 {
+            read_bloom(&mut r)
+        } else {
+            BloomFilter::empty()
+        };
 
         Some(ChunkHeader {
             ty: rc.ty,
@@ -96,6 +205,12 @@ impl FromReader for Option<ChunkHeader> {
             heap_size: rc.heap_size,
             compressed_size: rc.compressed_size,
             table_type: TableType::from_u8(rc.table_type),
+            bloom,
+            codec: rc.codec,
+            pkey_bloom,
+            restart_encoded: rc.version >= 4 && rc.restart_encoded != 0,
+            key_delta_encoded: rc.version >= 5 && rc.key_delta_encoded != 0,
+            column_zonemaps,
         })
     }
 }
@@ -137,21 +252,32 @@ impl Default for CHValue {
                 limits: Default::default(),
                 compressed_size: 0,
                 table_type: TableType::Data,
+                bloom: BloomFilter::empty(),
+                codec: 0,
+                pkey_bloom: BloomFilter::empty(),
+                restart_encoded: false,
+                key_delta_encoded: false,
+                column_zonemaps: Vec::new(),
             },
             location: 0,
         }
     }
 }
 
+// Keyed on `pkey`'s `TypeData::encode_memcmp()` bytes rather than `TypeData` itself, so ordering
+// `ChunkHeaderIndex`'s `BTreeMap` (and binary-searching it via `range`) is a plain `Vec<u8>`
+// comparison -- exactly `memcmp` semantics, by `encode_memcmp`'s own invariant -- instead of
+// dispatching through `TypeData::cmp`. `pkey` itself is never retained, so nothing here depends
+// on a `TypeData` that still needs heap resolution.
 #[derive(Debug, Clone, PartialEq, Eq, Ord)]
 pub struct MinKey {
     ty: u16,
-    pkey: TypeData,
+    pkey_encoded: Vec<u8>,
 }
 
 impl PartialOrd for MinKey {
     fn partial_cmp(&self, other: &Self) -> Option<Ordering> {
-        Some(self.ty.cmp(&other.ty).then(self.pkey.cmp(&other.pkey)))
+        Some(self.ty.cmp(&other.ty).then(self.pkey_encoded.cmp(&other.pkey_encoded)))
     }
 }
 
@@ -159,13 +285,13 @@ impl MinKey {
     pub fn start_ty(&self) -> MinKey {
         MinKey {
             ty: self.ty,
-            pkey: TypeData::Null,
+            pkey_encoded: TypeData::Null.encode_memcmp(),
         }
     }
     pub fn new(ty: u64, pkey: TypeData) -> MinKey {
         MinKey {
             ty: ty as u16,
-            pkey,
+            pkey_encoded: pkey.encode_memcmp(),
         }
     }
 }
@@ -225,13 +351,22 @@ impl ChunkHeaderIndex {
         prev.ch.limits = new_limit;
         self.push(prev.location, prev.ch);
     }
+
+    // Like `reset_limits`, but for the shrunk page's zone map after a split: `split` recomputes
+    // exact stats for both halves (see `TableBase2::split`), so the cached `ChunkHeader` here
+    // needs the same update the in-memory page already got, or `get_in_all_by_zonemap` keeps
+    // pruning against the pre-split, too-wide interval until this page's next flush.
+    pub fn reset_column_zonemaps(&mut self, ty: u64, min: TypeData, new_zonemaps: Vec<Range<TypeData>>) {
+        let x = self.get_in_one_mut(ty, min).next().unwrap();
+        x.1.ch.column_zonemaps = new_zonemaps;
+    }
     pub fn update_limits(&mut self, ty: u64, loc: u64, pkey: TypeData) {
         let x = self.get_in_one_mut(ty, pkey.clone()).next().unwrap();
         assert_eq!(x.1.location, loc);
         let x0 = x.0.clone();
 
         // Since we're changing the lower bound, have to reindex in CH (as that btree is sorted by lower bound)
-        if x.0.pkey > pkey {
+        if x.0.pkey_encoded > pkey.encode_memcmp() {
             let mut new_limit = x.1.ch.limits.clone();
             new_limit.add(&pkey);
             let mut value = self.0.remove(&x0).unwrap();
@@ -243,6 +378,20 @@ impl ChunkHeaderIndex {
             x.1.ch.limits.add(&pkey);
         }
     }
+
+    // `TableBase2::insert_tb` widens the *in-memory page's* `column_zonemaps` as rows come in,
+    // but the cached `ChunkHeader` this index holds for an already-flushed page is a separate
+    // copy and doesn't see that -- without this, `get_in_all_by_zonemap` would keep pruning
+    // against a stale interval until the page's next flush, silently dropping rows an equality
+    // filter should have found. No reindexing needed here (unlike `update_limits`): zone maps
+    // don't participate in the btree's sort key.
+    pub fn widen_column_zonemaps(&mut self, ty: u64, loc: u64, pkey: TypeData, fields: &[TypeData]) {
+        let x = self.get_in_one_mut(ty, pkey).next().unwrap();
+        assert_eq!(x.1.location, loc);
+        for (zonemap, field) in x.1.ch.column_zonemaps.iter_mut().zip(fields.iter()) {
+            zonemap.add(field);
+        }
+    }
 }
 
 impl FromReader for ChunkHeader {
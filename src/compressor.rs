@@ -1,6 +1,125 @@
 
 use crate::SuitableDataType;
 
+// Which algorithm (if any) a page body was compressed with; stored as a raw byte in
+// `ChunkHeader::codec`. `Zstd`'s level only matters at encode time -- the decoder doesn't
+// need it, so `from_u8` reconstructs a canonical `Zstd(0)` regardless of what level was
+// originally chosen; the on-disk byte alone is still enough to decode correctly.
+#[derive(Debug, Copy, Clone, PartialEq, Eq)]
+pub enum Codec {
+    None,
+    Lz4,
+    Zstd(i32),
+    Snappy,
+}
+
+impl Codec {
+    pub fn to_u8(self) -> u8 {
+        match self {
+            Codec::None => 0,
+            Codec::Lz4 => 1,
+            Codec::Zstd(_) => 2,
+            Codec::Snappy => 3,
+        }
+    }
+    pub fn from_u8(v: u8) -> Self {
+        match v {
+            0 => Codec::None,
+            1 => Codec::Lz4,
+            2 => Codec::Zstd(0),
+            3 => Codec::Snappy,
+            _ => panic!("Unknown codec byte {}", v),
+        }
+    }
+}
+
+// Compresses a page body (row data + heap, concatenated) with the given codec.
+pub fn compress_body(codec: Codec, data: &[u8]) -> Vec<u8> {
+    match codec {
+        Codec::None => data.to_vec(),
+        Codec::Zstd(level) => zstd::stream::encode_all(data, level).unwrap(),
+        Codec::Lz4 => rle_compress(data),
+        Codec::Snappy => snappy_compress(data),
+    }
+}
+
+pub fn decompress_body(codec: Codec, data: &[u8]) -> Vec<u8> {
+    match codec {
+        Codec::None => data.to_vec(),
+        Codec::Zstd(_) => zstd::stream::decode_all(data).unwrap(),
+        Codec::Lz4 => rle_decompress(data),
+        Codec::Snappy => snappy_decompress(data),
+    }
+}
+
+// Stand-in for a real LZ4 codec -- no lz4 crate is vendored in this tree. A simple
+// byte-oriented run-length encoder: `[run_len: u8][byte]` pairs, runs capped at 255.
+fn rle_compress(data: &[u8]) -> Vec<u8> {
+    let mut out = Vec::new();
+    let mut i = 0;
+    while i < data.len() {
+        let byte = data[i];
+        let mut run = 1usize;
+        while run < 255 && i + run < data.len() && data[i + run] == byte {
+            run += 1;
+        }
+        out.push(run as u8);
+        out.push(byte);
+        i += run;
+    }
+    out
+}
+
+fn rle_decompress(data: &[u8]) -> Vec<u8> {
+    let mut out = Vec::new();
+    for pair in data.chunks_exact(2) {
+        out.resize(out.len() + pair[0] as usize, pair[1]);
+    }
+    out
+}
+
+// Stand-in for a real Snappy codec -- no snappy crate is vendored in this tree. This
+// crate's page bodies are mostly fixed-width struct data run through `shuffle_bytes` first,
+// which groups together the high-order bytes of small integers -- usually long zero runs.
+// A zero-run eliminator captures most of that win cheaply: alternating
+// `[zero_run_len: u32][literal_len: u32][literal bytes]` records.
+fn snappy_compress(data: &[u8]) -> Vec<u8> {
+    let mut out = Vec::new();
+    let mut i = 0;
+    while i < data.len() {
+        let zero_start = i;
+        while i < data.len() && data[i] == 0 {
+            i += 1;
+        }
+        let zero_len = (i - zero_start) as u32;
+
+        let lit_start = i;
+        while i < data.len() && data[i] != 0 {
+            i += 1;
+        }
+        let lit_len = (i - lit_start) as u32;
+
+        out.extend_from_slice(&zero_len.to_le_bytes());
+        out.extend_from_slice(&lit_len.to_le_bytes());
+        out.extend_from_slice(&data[lit_start..i]);
+    }
+    out
+}
+
+fn snappy_decompress(data: &[u8]) -> Vec<u8> {
+    let mut out = Vec::new();
+    let mut i = 0;
+    while i < data.len() {
+        let zero_len = u32::from_le_bytes(data[i..i + 4].try_into().unwrap());
+        let lit_len = u32::from_le_bytes(data[i + 4..i + 8].try_into().unwrap());
+        i += 8;
+        out.resize(out.len() + zero_len as usize, 0);
+        out.extend_from_slice(&data[i..i + lit_len as usize]);
+        i += lit_len as usize;
+    }
+    out
+}
+
 fn shuffle_bytes(bytes: &[u8], type_len: usize) -> Vec<u8> {
     assert_eq!(bytes.len() % type_len, 0);
     let tuples = bytes.len() / type_len;
@@ -41,21 +160,33 @@ fn recover_structs<T: SuitableDataType>(bytes: &[u8]) -> Vec<u8> {
     reassemble_bytes(bytes, T::TYPE_SIZE as usize)
 }
 
-pub fn compress<T: SuitableDataType>(structs: &[u8]) -> Vec<u8> {
+// Runtime-sized counterparts of `compress`/`decompress`, for callers (e.g. `TableBase2`) that
+// only know their row width as a `usize` rather than a `SuitableDataType` type parameter.
+pub fn compress_dyn(codec: Codec, structs: &[u8], type_size: usize) -> Vec<u8> {
+    let shuffled = shuffle_bytes(structs, type_size);
+    compress_body(codec, &shuffled)
+}
+
+pub fn decompress_dyn(codec: Codec, bytes: &[u8], type_size: usize) -> Vec<u8> {
+    let decompressed = decompress_body(codec, bytes);
+    reassemble_bytes(&decompressed, type_size)
+}
+
+pub fn compress<T: SuitableDataType>(codec: Codec, structs: &[u8]) -> Vec<u8> {
     let shuffled = shuffle_struct::<T>(structs);
-    
-    zstd::stream::encode_all(&*shuffled, 0).unwrap()
+
+    compress_body(codec, &shuffled)
 }
-pub fn compress_heap(data: &[u8]) -> Vec<u8> {
-    zstd::stream::encode_all(data, 0).unwrap()
+pub fn compress_heap(codec: Codec, data: &[u8]) -> Vec<u8> {
+    compress_body(codec, data)
 }
 
-pub fn decompress_heap(data: &[u8]) -> Vec<u8> {
-    zstd::stream::decode_all(data).unwrap()
+pub fn decompress_heap(codec: Codec, data: &[u8]) -> Vec<u8> {
+    decompress_body(codec, data)
 }
-pub fn decompress<T: SuitableDataType>(bytes: &[u8]) -> Vec<u8> {
-    let decompressed = zstd::stream::decode_all(bytes).unwrap();
-    
+pub fn decompress<T: SuitableDataType>(codec: Codec, bytes: &[u8]) -> Vec<u8> {
+    let decompressed = decompress_body(codec, bytes);
+
     recover_structs::<T>(&decompressed)
 }
 
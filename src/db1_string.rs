@@ -126,6 +126,15 @@ impl Db1String {
             Self::Ptr(_, _) => panic!()
         }
     }
+    // Copies a borrowed `Ptr` into an owned `Resolvedo` buffer, detaching it from the
+    // lifetime of whatever page/heap it was pointing into. No-op for already-owned strings.
+    pub fn to_owned(&mut self) {
+        match self {
+            Self::Resolvedo(_v) => {}
+            Self::Ptr(..) => *self = Self::Resolvedo(self.as_buffer().to_vec()),
+            Self::Unresolved(..) => panic!("resolve_item against the backing heap first"),
+        }
+    }
 }
 
 impl From<String> for Db1String {
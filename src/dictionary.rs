@@ -0,0 +1,79 @@
+// Global string-interning table backing dictionary-encoded columns (`Type::Dictionary`): maps
+// each distinct byte string to a compact `u32` symbol id, with canonical bytes held in an
+// append-only arena so a low-cardinality column (MIME types, status strings, repeated labels...)
+// stores each distinct value once instead of once per row. `NamedTables` persists new entries as
+// rows in a "dictionary" system table and rebuilds this structure from it on load, the same way
+// `schema`/`index_schema` rebuild their in-memory structures from their own system tables.
+
+use std::collections::HashMap;
+
+#[derive(Debug, Default)]
+pub struct Dictionary {
+    index: HashMap<Vec<u8>, u32>,
+    arena: Vec<u8>,
+    // (offset, len) into `arena` per symbol id -- id is just the index into this `Vec`.
+    spans: Vec<(u32, u32)>,
+}
+
+impl Dictionary {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    // Returns `value`'s symbol id, interning it as a new symbol if it hasn't been seen before.
+    // The bool says whether a new symbol was created, so callers only persist genuinely new
+    // entries to the on-disk dictionary table.
+    pub fn intern(&mut self, value: &[u8]) -> (u32, bool) {
+        if let Some(&id) = self.index.get(value) {
+            return (id, false);
+        }
+        let id = self.spans.len() as u32;
+        let offset = self.arena.len() as u32;
+        self.arena.extend_from_slice(value);
+        self.spans.push((offset, value.len() as u32));
+        self.index.insert(value.to_vec(), id);
+        (id, true)
+    }
+
+    pub fn resolve(&self, id: u32) -> &[u8] {
+        let (offset, len) = self.spans[id as usize];
+        &self.arena[offset as usize..offset as usize + len as usize]
+    }
+
+    // Re-inserts a `(id, value)` pair recovered from the on-disk dictionary table while
+    // reloading, preserving its original id rather than assigning a fresh one. Rows must be
+    // restored in ascending id order, which is how `NamedTables` scans them back (the
+    // dictionary table's primary key is the symbol id).
+    pub fn restore(&mut self, id: u32, value: &[u8]) {
+        assert_eq!(id as usize, self.spans.len(), "dictionary rows must be restored in id order");
+        let offset = self.arena.len() as u32;
+        self.arena.extend_from_slice(value);
+        self.spans.push((offset, value.len() as u32));
+        self.index.insert(value.to_vec(), id);
+    }
+}
+
+#[test]
+fn test_dictionary_interns_repeated_values() {
+    let mut dict = Dictionary::new();
+    let (a, a_new) = dict.intern(b"application/pdf");
+    let (b, b_new) = dict.intern(b"application/json");
+    let (a2, a2_new) = dict.intern(b"application/pdf");
+    assert!(a_new);
+    assert!(b_new);
+    assert!(!a2_new);
+    assert_eq!(a, a2);
+    assert_ne!(a, b);
+    assert_eq!(dict.resolve(a), b"application/pdf");
+    assert_eq!(dict.resolve(b), b"application/json");
+}
+
+#[test]
+fn test_dictionary_restore_preserves_ids() {
+    let mut dict = Dictionary::new();
+    dict.restore(0, b"x");
+    dict.restore(1, b"y");
+    assert_eq!(dict.resolve(0), b"x");
+    assert_eq!(dict.resolve(1), b"y");
+    assert_eq!(dict.intern(b"x"), (0, false));
+}
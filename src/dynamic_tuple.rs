@@ -58,7 +58,13 @@ impl TupleBuilder {
         assert_eq!(self.fields.len(), ty.fields.len());
         for a in self.fields.iter().zip(ty.fields.iter()) {
             match a {
-                (TypeData::Int(..), Type::Int) | (TypeData::String(..), Type::String) => {}
+                (TypeData::Int(..), Type::Int)
+                | (TypeData::String(..), Type::String)
+                | (TypeData::Symbol(..), Type::Dictionary)
+                | (TypeData::Float(..), Type::Float)
+                | (TypeData::Bool(..), Type::Bool)
+                | (TypeData::Bytes(..), Type::Bytes)
+                | (TypeData::Uuid(..), Type::Uuid) => {}
                 _ => return false,
             }
         }
@@ -79,6 +85,16 @@ impl TupleBuilder {
     pub fn extract(&self, ind: usize) -> &TypeData {
         &self.fields[ind]
     }
+    // Detach any borrowed `Db1String::Ptr` fields (from `DynamicTuple::read_tuple_borrowed`)
+    // into owned buffers, so the tuple can safely outlive the page it was read from.
+    pub fn to_owned(mut self) -> Self {
+        for f in &mut self.fields {
+            if let TypeData::String(s) = f {
+                s.to_owned();
+            }
+        }
+        self
+    }
     pub fn add_int(mut self, i: u64) -> Self {
         self.fields.push(TypeData::Int(i));
         self
@@ -100,6 +116,21 @@ impl TupleBuilder {
                 TypeData::String(s) => {
                     s.serialize_with_heap(&mut writer, &mut heap);
                 }
+                TypeData::Symbol(id) => {
+                    writer.write_all(&id.to_le_bytes()).unwrap();
+                }
+                TypeData::Float(f) => {
+                    writer.write_all(&f.to_le_bytes()).unwrap();
+                }
+                TypeData::Bool(b) => {
+                    writer.write_all(&[*b as u8]).unwrap();
+                }
+                TypeData::Bytes(s) => {
+                    s.serialize_with_heap(&mut writer, &mut heap);
+                }
+                TypeData::Uuid(id) => {
+                    writer.write_all(id).unwrap();
+                }
                 _ => panic!(),
             }
         }
@@ -109,6 +140,18 @@ impl TupleBuilder {
             len: len as usize,
         }
     }
+
+    // Order-preserving byte encoding: concatenates `TypeData::encode_memcmp` for each field, so
+    // two tuples' `build_sortable()` outputs compare in logical tuple order under a raw memcmp.
+    // Unlike `build`, there's no heap indirection (strings are inlined, escaped and
+    // zero-terminated) and no fixed-size buffer, since a memcmp-encoded string's length varies.
+    pub fn build_sortable(&self) -> Vec<u8> {
+        let mut out = Vec::new();
+        for field in &self.fields {
+            out.extend_from_slice(&field.encode_memcmp());
+        }
+        out
+    }
 }
 
 impl DynamicTuple {
@@ -117,15 +160,41 @@ impl DynamicTuple {
         Self { fields: v }
     }
     pub fn size(&self) -> u64 {
-        self.fields
-            .iter()
-            .map(|v| match v {
-                Type::Int => 8,
-                Type::String => Db1String::TYPE_SIZE,
-            })
-            .sum()
+        self.layout().total_width
     }
-    pub fn read_tuple(&self, a: &[u8], mut load_columns: u64, heap: &[u8]) -> TupleBuilder {
+    // On-disk offset/width/heap-indirection of each field, computed from the field types
+    // rather than `std::mem::size_of` or a hand-maintained constant.
+    pub fn layout(&self) -> crate::layout::RecordLayout {
+        crate::layout::compute_layout(&self.fields)
+    }
+    pub fn read_tuple(&self, a: &[u8], load_columns: u64, heap: &[u8]) -> TupleBuilder {
+        self.read_tuple_impl(a, load_columns, heap, true)
+    }
+
+    // Zero-copy variant of `read_tuple`: strings stay as `Db1String::Ptr` borrowing `heap`
+    // instead of being copied into an owned `Resolvedo` buffer. Cheap for scans that only
+    // inspect or compare rows; callers that need to keep a tuple past the lifetime of `heap`
+    // (e.g. past a page being unloaded) must call `TupleBuilder::to_owned` on it first.
+    pub fn read_tuple_borrowed(&self, a: &[u8], load_columns: u64, heap: &[u8]) -> TupleBuilder {
+        self.read_tuple_impl(a, load_columns, heap, false)
+    }
+
+    // Inverse of `TupleBuilder::build_sortable`: walks `buf` field-by-field with
+    // `TypeData::decode_memcmp`, which self-delimits (the type tag, plus for strings the
+    // escaped zero-terminator) so no heap or fixed-width layout is needed to know where one
+    // field ends and the next begins.
+    pub fn read_tuple_sortable(&self, buf: &[u8]) -> TupleBuilder {
+        let mut pos = 0;
+        let mut fields = Vec::with_capacity(self.fields.len());
+        for _ in 0..self.fields.len() {
+            let (value, consumed) = TypeData::decode_memcmp(&buf[pos..]);
+            pos += consumed;
+            fields.push(value);
+        }
+        TupleBuilder { fields }
+    }
+
+    fn read_tuple_impl(&self, a: &[u8], mut load_columns: u64, heap: &[u8], owned: bool) -> TupleBuilder {
         if load_columns == 0 {
             load_columns = u64::MAX;
         }
@@ -145,14 +214,67 @@ impl DynamicTuple {
                     }
                 }
                 Type::String => {
-                    let mut data = Db1String::from_reader_and_heap(&mut slice, heap);
+                    let data = Db1String::from_reader_and_heap(&mut slice, heap);
                     if fully_load {
-                        data.resolve_item(heap);
+                        let data = if owned {
+                            let mut data = data;
+                            data.resolve_item(heap);
+                            data
+                        } else {
+                            data.to_ptr(heap)
+                        };
                         answer.push(TypeData::String(data));
                     } else {
                         answer.push(TypeData::Null)
                     }
                 }
+                Type::Dictionary => {
+                    let id = u32::from_le_bytes(read_to_buf(&mut slice));
+                    if fully_load {
+                        answer.push(TypeData::Symbol(id));
+                    } else {
+                        answer.push(TypeData::Null)
+                    }
+                }
+                Type::Float => {
+                    let f = f64::from_le_bytes(read_to_buf(&mut slice));
+                    if fully_load {
+                        answer.push(TypeData::Float(f));
+                    } else {
+                        answer.push(TypeData::Null)
+                    }
+                }
+                Type::Bool => {
+                    let b: [u8; 1] = read_to_buf(&mut slice);
+                    if fully_load {
+                        answer.push(TypeData::Bool(b[0] != 0));
+                    } else {
+                        answer.push(TypeData::Null)
+                    }
+                }
+                Type::Bytes => {
+                    let data = Db1String::from_reader_and_heap(&mut slice, heap);
+                    if fully_load {
+                        let data = if owned {
+                            let mut data = data;
+                            data.resolve_item(heap);
+                            data
+                        } else {
+                            data.to_ptr(heap)
+                        };
+                        answer.push(TypeData::Bytes(data));
+                    } else {
+                        answer.push(TypeData::Null)
+                    }
+                }
+                Type::Uuid => {
+                    let id: [u8; 16] = read_to_buf(&mut slice);
+                    if fully_load {
+                        answer.push(TypeData::Uuid(id));
+                    } else {
+                        answer.push(TypeData::Null)
+                    }
+                }
             }
         }
         TupleBuilder { fields: answer }
@@ -226,9 +348,32 @@ pub unsafe extern "C" fn sql_exec(
                             std::str::from_utf8(s.as_buffer()).unwrap()
                         ))
                         .unwrap(),
-                    TypeData::Null => {
-                        output_string.write_fmt(format_args!("{}", 0)).unwrap()
+                    TypeData::Null => output_string.write_str("null").unwrap(),
+                    TypeData::Symbol(id) => output_string.write_fmt(format_args!("{}", id)).unwrap(),
+                    TypeData::Float(f) => {
+                        // JSON has no NaN/Infinity literal; fall back to null rather than
+                        // emitting a token that would fail to parse.
+                        if f.is_finite() {
+                            output_string.write_fmt(format_args!("{}", f)).unwrap()
+                        } else {
+                            output_string.write_str("null").unwrap()
+                        }
+                    }
+                    TypeData::Bool(b) => output_string.write_fmt(format_args!("{}", b)).unwrap(),
+                    TypeData::Bytes(s) => {
+                        output_string.write_str("\"").unwrap();
+                        for b in s.as_buffer() {
+                            output_string.write_fmt(format_args!("{:02x}", b)).unwrap();
+                        }
+                        output_string.write_str("\"").unwrap();
                     }
+                    TypeData::Uuid(id) => output_string
+                        .write_fmt(format_args!(
+                            "\"{:02x}{:02x}{:02x}{:02x}-{:02x}{:02x}-{:02x}{:02x}-{:02x}{:02x}-{:02x}{:02x}{:02x}{:02x}{:02x}{:02x}\"",
+                            id[0], id[1], id[2], id[3], id[4], id[5], id[6], id[7],
+                            id[8], id[9], id[10], id[11], id[12], id[13], id[14], id[15]
+                        ))
+                        .unwrap(),
                 };
             }
             output_string.write_str("]").unwrap();
@@ -242,7 +387,7 @@ pub unsafe extern "C" fn sql_exec(
 
 impl<W: RWS> DynamicTable<W> {
     fn new(w: W) -> Self {
-        let mut ps = PageSerializer::smart_create(w);
+        let mut ps = PageSerializer::smart_create(w, None);
         Self {
             table: NamedTables::new(&mut ps),
             ps,
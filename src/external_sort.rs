@@ -0,0 +1,254 @@
+// Disk-spilling external merge sort, so sorting a result set larger than the buffer pool doesn't
+// require holding the whole thing in memory at once. Incoming tuples are buffered up to a byte
+// budget; each full buffer is sorted in memory and flushed to a scratch file as length-prefixed
+// tuples ("a run"). Once the input is exhausted, the runs are merged with a k-way min-heap -- one
+// cursor per run, so each run is read back from disk one tuple at a time. The merged output
+// itself is collected into the `Vec` that `external_sort` returns, so the whole sorted result is
+// resident in memory once the merge finishes.
+//
+// `NamedTables::execute_select`'s `ORDER BY` no longer calls into this module directly -- it now
+// goes through `ra_ops::OrderBy`, which spills runs as `PageSerializer` pages instead of standalone
+// scratch files (reusing `serialize_tuple`/`deserialize_tuple` from here for the on-disk tuple
+// framing) and exposes a pull-based `next()` so a caller *could* consume the merge one tuple at a
+// time -- though today's only callers immediately drain it into a `Vec` anyway. This module's own
+// `external_sort`/`sort` are kept as a tested, simpler, file-backed sort for any caller that
+// doesn't need page-backed storage.
+
+use std::cmp::Ordering;
+use std::collections::BinaryHeap;
+use std::fs::File;
+use std::io::{BufReader, BufWriter, ErrorKind, Read, Write};
+use std::path::PathBuf;
+use std::sync::atomic::{AtomicU64, Ordering as AtomicOrdering};
+
+use dynamic_tuple::TupleBuilder;
+use type_data::TypeData;
+
+// Default per-run byte budget before a run is sorted and spilled to disk. Callers that know
+// their result sets are small (or want tighter memory control) can pass their own via
+// `external_sort`.
+pub const DEFAULT_RUN_BYTE_BUDGET: usize = 4 * 1024 * 1024;
+
+// Tuples are serialized with `TupleBuilder::build_sortable` (itself just `TypeData::encode_memcmp`
+// per field), which is already a self-delimiting, heap-free byte encoding -- exactly what a
+// scratch file needs, since there's no page heap around to resolve `Db1String::Unresolved`
+// payloads against once they've been written to disk. The field count is prefixed separately
+// since, unlike `DynamicTuple::read_tuple_sortable`, this has no schema to read it back against.
+// `pub(crate)` rather than private -- `ra_ops::OrderBy` reuses this exact framing for its own
+// scratch runs (spilled as `PageSerializer` pages instead of this module's standalone files), so
+// both readers agree on one on-disk tuple format.
+pub(crate) fn serialize_tuple(t: &TupleBuilder) -> Vec<u8> {
+    let mut buf = Vec::new();
+    buf.extend_from_slice(&(t.fields.len() as u32).to_le_bytes());
+    buf.extend_from_slice(&t.build_sortable());
+    buf
+}
+
+pub(crate) fn deserialize_tuple(buf: &[u8]) -> TupleBuilder {
+    let count = u32::from_le_bytes(buf[0..4].try_into().unwrap()) as usize;
+    let mut pos = 4;
+    let mut fields = Vec::with_capacity(count);
+    for _ in 0..count {
+        let (value, consumed) = TypeData::decode_memcmp(&buf[pos..]);
+        pos += consumed;
+        fields.push(value);
+    }
+    TupleBuilder { fields }
+}
+
+static SCRATCH_COUNTER: AtomicU64 = AtomicU64::new(0);
+
+fn scratch_path() -> PathBuf {
+    let n = SCRATCH_COUNTER.fetch_add(1, AtomicOrdering::Relaxed);
+    std::env::temp_dir().join(format!("db1_sort_run_{}_{}.tmp", std::process::id(), n))
+}
+
+fn compare_rows(a: &TupleBuilder, b: &TupleBuilder, column: usize, descending: bool) -> Ordering {
+    let ord = a.fields[column].cmp(&b.fields[column]);
+    if descending { ord.reverse() } else { ord }
+}
+
+// One sorted run spilled to a scratch file. Deletes its file on drop, so a merge that's
+// abandoned partway through (panic, early return) doesn't leak temp files.
+struct Run {
+    reader: BufReader<File>,
+    path: PathBuf,
+}
+
+impl Run {
+    fn next_tuple(&mut self) -> Option<TupleBuilder> {
+        let mut len_buf = [0u8; 4];
+        match self.reader.read_exact(&mut len_buf) {
+            Ok(()) => {}
+            Err(e) if e.kind() == ErrorKind::UnexpectedEof => return None,
+            Err(e) => panic!("error reading sort run {:?}: {}", self.path, e),
+        }
+        let len = u32::from_le_bytes(len_buf) as usize;
+        let mut buf = vec![0u8; len];
+        self.reader.read_exact(&mut buf).unwrap();
+        Some(deserialize_tuple(&buf))
+    }
+}
+
+impl Drop for Run {
+    fn drop(&mut self) {
+        let _ = std::fs::remove_file(&self.path);
+    }
+}
+
+fn flush_run(mut tuples: Vec<TupleBuilder>, column: usize, descending: bool) -> Run {
+    tuples.sort_by(|a, b| compare_rows(a, b, column, descending));
+
+    let path = scratch_path();
+    let mut writer = BufWriter::new(File::create(&path).unwrap());
+    for t in &tuples {
+        let bytes = serialize_tuple(t);
+        writer.write_all(&(bytes.len() as u32).to_le_bytes()).unwrap();
+        writer.write_all(&bytes).unwrap();
+    }
+    writer.flush().unwrap();
+
+    Run { reader: BufReader::new(File::open(&path).unwrap()), path }
+}
+
+// A run's current head tuple, ordered so that `BinaryHeap` (a max-heap) pops the tuple that
+// should come out next in the requested sort direction.
+struct HeapEntry {
+    key: TypeData,
+    descending: bool,
+    run_index: usize,
+    tuple: TupleBuilder,
+}
+
+impl PartialEq for HeapEntry {
+    fn eq(&self, other: &Self) -> bool {
+        self.key == other.key
+    }
+}
+impl Eq for HeapEntry {}
+
+impl PartialOrd for HeapEntry {
+    fn partial_cmp(&self, other: &Self) -> Option<Ordering> {
+        Some(self.cmp(other))
+    }
+}
+
+impl Ord for HeapEntry {
+    fn cmp(&self, other: &Self) -> Ordering {
+        // Ascending output wants the smallest key first, but `BinaryHeap` always pops the
+        // max -- so ascending reverses the comparison and descending (which wants the largest
+        // key first) uses it as-is.
+        if self.descending {
+            self.key.cmp(&other.key)
+        } else {
+            other.key.cmp(&self.key)
+        }
+    }
+}
+
+fn merge_runs(mut runs: Vec<Run>, column: usize, descending: bool) -> Vec<TupleBuilder> {
+    let mut heap = BinaryHeap::new();
+    for (run_index, run) in runs.iter_mut().enumerate() {
+        if let Some(tuple) = run.next_tuple() {
+            let key = tuple.fields[column].clone();
+            heap.push(HeapEntry { key, descending, run_index, tuple });
+        }
+    }
+
+    let mut output = Vec::new();
+    while let Some(HeapEntry { run_index, tuple, .. }) = heap.pop() {
+        output.push(tuple);
+        if let Some(next_tuple) = runs[run_index].next_tuple() {
+            let key = next_tuple.fields[column].clone();
+            heap.push(HeapEntry { key, descending, run_index, tuple: next_tuple });
+        }
+    }
+    output
+}
+
+// Sorts `tuples` by `column` (ascending unless `descending`), spilling to scratch files under
+// `run_byte_budget` bytes per run whenever the input doesn't fit in memory at once. Input that
+// fits in a single run never touches disk.
+pub fn external_sort(
+    tuples: impl Iterator<Item = TupleBuilder>,
+    column: usize,
+    descending: bool,
+    run_byte_budget: usize,
+) -> Vec<TupleBuilder> {
+    let mut runs: Vec<Run> = Vec::new();
+    let mut buffer: Vec<TupleBuilder> = Vec::new();
+    let mut buffer_bytes = 0usize;
+
+    for tuple in tuples {
+        buffer_bytes += serialize_tuple(&tuple).len();
+        buffer.push(tuple);
+        if buffer_bytes >= run_byte_budget {
+            runs.push(flush_run(std::mem::take(&mut buffer), column, descending));
+            buffer_bytes = 0;
+        }
+    }
+
+    if runs.is_empty() {
+        buffer.sort_by(|a, b| compare_rows(a, b, column, descending));
+        return buffer;
+    }
+
+    if !buffer.is_empty() {
+        runs.push(flush_run(buffer, column, descending));
+    }
+
+    merge_runs(runs, column, descending)
+}
+
+pub fn sort(tuples: impl Iterator<Item = TupleBuilder>, column: usize, descending: bool) -> Vec<TupleBuilder> {
+    external_sort(tuples, column, descending, DEFAULT_RUN_BYTE_BUDGET)
+}
+
+#[test]
+fn test_external_sort_single_run_ascending() {
+    let tuples = vec![
+        TupleBuilder { fields: vec![TypeData::Int(3)] },
+        TupleBuilder { fields: vec![TypeData::Int(1)] },
+        TupleBuilder { fields: vec![TypeData::Int(2)] },
+    ];
+    let sorted = sort(tuples.into_iter(), 0, false);
+    let ints: Vec<u64> = sorted.into_iter().map(|t| t.first()).collect();
+    assert_eq!(ints, vec![1, 2, 3]);
+}
+
+#[test]
+fn test_external_sort_descending() {
+    let tuples = vec![
+        TupleBuilder { fields: vec![TypeData::Int(3)] },
+        TupleBuilder { fields: vec![TypeData::Int(1)] },
+        TupleBuilder { fields: vec![TypeData::Int(2)] },
+    ];
+    let sorted = sort(tuples.into_iter(), 0, true);
+    let ints: Vec<u64> = sorted.into_iter().map(|t| t.first()).collect();
+    assert_eq!(ints, vec![3, 2, 1]);
+}
+
+#[test]
+fn test_external_sort_spills_across_multiple_runs() {
+    let tuples: Vec<_> = (0..50)
+        .rev()
+        .map(|i| TupleBuilder { fields: vec![TypeData::Int(i), TypeData::String(format!("row{}", i).into())] })
+        .collect();
+
+    // Force a new run roughly every handful of tuples so the merge path is actually exercised.
+    let sorted = external_sort(tuples.into_iter(), 0, false, 64);
+    let ints: Vec<u64> = sorted.into_iter().map(|t| t.first()).collect();
+    let expected: Vec<u64> = (0..50).collect();
+    assert_eq!(ints, expected);
+}
+
+#[test]
+fn test_external_sort_preserves_string_column() {
+    let tuples = vec![
+        TupleBuilder { fields: vec![TypeData::Int(2), TypeData::String("b".into())] },
+        TupleBuilder { fields: vec![TypeData::Int(1), TypeData::String("a".into())] },
+    ];
+    let sorted = external_sort(tuples.into_iter(), 0, false, 8);
+    assert_eq!(sorted[0].fields[1], TypeData::String("a".into()));
+    assert_eq!(sorted[1].fields[1], TypeData::String("b".into()));
+}
@@ -0,0 +1,128 @@
+// Size-classed free-space allocator for variable-length pages. `PageSerializer::add_page` used
+// to only reuse freed regions for constant-size tables (an exact-size pop off the back of a flat
+// `Vec`); everywhere else `deleted` entries just accumulated forever and the file only grew. This
+// buckets freed `(pos, len)` regions by power-of-two size class so a fitting region can be found
+// without scanning every free entry, and coalesces adjacent free regions on insert so repeated
+// alloc/free doesn't fragment the file into slivers nothing can reuse.
+
+use std::collections::{BTreeMap, HashMap};
+
+// Smallest size class whose pages can hold `len` bytes (i.e. `ceil(log2(max(len, 1)))`).
+fn size_class(len: u64) -> u32 {
+    64 - (len.max(1) - 1).leading_zeros()
+}
+
+#[derive(Debug, Default)]
+pub struct FreeList {
+    by_pos: BTreeMap<u64, u64>,      // pos -> len; the source of truth for what's free
+    buckets: HashMap<u32, Vec<u64>>, // size class -> positions (may hold stale entries, checked against by_pos)
+}
+
+impl FreeList {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    // Rebuilds the allocator from the `(pos, len)` pairs `iter_pages` collects while scanning
+    // `PageResult::Deleted` entries on reopen.
+    pub fn rebuild(entries: impl IntoIterator<Item = (u64, u64)>) -> Self {
+        let mut fl = Self::new();
+        for (pos, len) in entries {
+            fl.insert(pos, len);
+        }
+        fl
+    }
+
+    fn bucket_insert(&mut self, pos: u64, len: u64) {
+        self.by_pos.insert(pos, len);
+        self.buckets.entry(size_class(len)).or_default().push(pos);
+    }
+
+    fn bucket_remove(&mut self, pos: u64) -> Option<u64> {
+        let len = self.by_pos.remove(&pos)?;
+        if let Some(positions) = self.buckets.get_mut(&size_class(len)) {
+            positions.retain(|&p| p != pos);
+        }
+        Some(len)
+    }
+
+    // Inserts a newly-freed `(pos, len)` region, coalescing it with an immediately adjacent free
+    // region -- one ending exactly at `pos`, or one starting exactly at `pos + len` -- so
+    // fragmentation doesn't starve later large allocations.
+    pub fn insert(&mut self, pos: u64, len: u64) {
+        let mut pos = pos;
+        let mut len = len;
+
+        if let Some((&prev_pos, &prev_len)) = self.by_pos.range(..pos).next_back() {
+            if prev_pos + prev_len == pos {
+                self.bucket_remove(prev_pos);
+                pos = prev_pos;
+                len += prev_len;
+            }
+        }
+
+        if let Some(&next_len) = self.by_pos.get(&(pos + len)) {
+            self.bucket_remove(pos + len);
+            len += next_len;
+        }
+
+        self.bucket_insert(pos, len);
+    }
+
+    // Finds and removes the smallest free region that fits `needed` bytes, returning its
+    // `(pos, len)` -- `len >= needed`, and the caller is responsible for splitting any leftover
+    // remainder back in via `insert`.
+    pub fn allocate(&mut self, needed: u64) -> Option<(u64, u64)> {
+        let mut best: Option<(u64, u64)> = None;
+        for class in size_class(needed)..=64 {
+            if let Some(positions) = self.buckets.get(&class) {
+                for &pos in positions {
+                    if let Some(&len) = self.by_pos.get(&pos) {
+                        if len >= needed && best.map_or(true, |(_, best_len)| len < best_len) {
+                            best = Some((pos, len));
+                        }
+                    }
+                }
+            }
+            // Size classes are powers of two -- any fit in the first non-empty class we find is
+            // as good a fit as we're going to get without a full scan.
+            if best.is_some() {
+                break;
+            }
+        }
+        if let Some((pos, _)) = best {
+            self.bucket_remove(pos);
+        }
+        best
+    }
+}
+
+#[test]
+fn test_free_list_allocate_first_fit() {
+    let mut fl = FreeList::new();
+    fl.insert(100, 16);
+    fl.insert(200, 64);
+
+    let (pos, len) = fl.allocate(20).unwrap();
+    assert_eq!((pos, len), (200, 64));
+    assert_eq!(fl.allocate(1000), None);
+}
+
+#[test]
+fn test_free_list_coalesces_adjacent_regions() {
+    let mut fl = FreeList::new();
+    fl.insert(0, 10);
+    fl.insert(10, 10);
+    // The two regions are adjacent, so they should merge into one 20-byte region.
+    assert_eq!(fl.allocate(15), Some((0, 20)));
+    assert_eq!(fl.allocate(1), None);
+}
+
+#[test]
+fn test_free_list_coalesces_both_neighbours() {
+    let mut fl = FreeList::new();
+    fl.insert(0, 10);
+    fl.insert(20, 10);
+    fl.insert(10, 10); // fills the gap, should merge all three into one 30-byte region
+    assert_eq!(fl.allocate(25), Some((0, 30)));
+}
@@ -101,5 +101,64 @@ impl HashDb {
         }
         result_buffer
     }
+
+    // Removes the `(value, location)` entry, returning whether it was found. Since there are no
+    // tombstones, the vacated slot has to be patched up with backward-shift deletion: walk the
+    // probe chain forward from the hole, and for each occupied slot whose *ideal* slot (its
+    // stored `hash`, before any probing) is still at or before the hole, slide it back -- that's
+    // exactly the slot a lookup for it would stop probing at once the hole is empty again. Stop
+    // at the first slot that's actually empty; nothing past it could have probed over the hole.
+    pub fn remove<T: Hash>(&mut self, value: T, location: u64) -> bool {
+        let target_hash = hash(&value);
+
+        let mut hole = target_hash;
+        loop {
+            match self.hash.get(&hole) {
+                Some(x) if x.hash == target_hash && x.pointer == location => break,
+                Some(_) => hole += 1,
+                None => return false,
+            }
+        }
+        self.hash.remove(&hole);
+
+        let mut probe = hole + 1;
+        while let Some(entry) = self.hash.get(&probe).cloned() {
+            if entry.hash <= hole {
+                self.hash.remove(&probe);
+                self.hash.insert(hole, entry);
+                hole = probe;
+            }
+            probe += 1;
+        }
+        true
+    }
+}
+
+#[test]
+fn test_remove_preserves_probe_chain() {
+    let mut db = HashDb::default();
+    let home = hash(&"probe-base");
+    // Force collisions by storing raw `IndexKey`s that all share the same home slot.
+    db.store_by_hash(IndexKey { hash: home, pointer: 1 });
+    db.store_by_hash(IndexKey { hash: home, pointer: 2 });
+    db.store_by_hash(IndexKey { hash: home, pointer: 3 });
+    assert_eq!(db.hash[&home].pointer, 1);
+    assert_eq!(db.hash[&(home + 1)].pointer, 2);
+    assert_eq!(db.hash[&(home + 2)].pointer, 3);
+
+    assert!(db.remove("probe-base", 1));
+
+    // The chain closes up: the slot 2 was probed into shifts back into the hole.
+    assert_eq!(db.hash[&home].pointer, 2);
+    assert_eq!(db.hash[&(home + 1)].pointer, 3);
+    assert!(!db.hash.contains_key(&(home + 2)));
+}
+
+#[test]
+fn test_remove_missing_entry_is_noop() {
+    let mut db = HashDb::default();
+    db.store(1u64, 10);
+    assert!(!db.remove(2u64, 10));
+    assert_eq!(db.get(1u64), vec![10]);
 }
 
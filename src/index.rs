@@ -1,6 +1,5 @@
 // Application specific
 
-use std::collections::HashSet;
 use std::ffi::CStr;
 use std::fmt::{Debug, Formatter};
 use std::fs::File;
@@ -12,10 +11,14 @@ use std::os::raw::c_char;
 
 use serializer::PageSerializer;
 
+use crate::bloom::BloomFilter;
 use crate::db1_string::Db1String;
 use crate::gen_suitable_data_type_impls;
-use crate::hash::HashDb;
-use crate::{BytesSerialize, FromReader, SuitableDataType, TableManager};
+use crate::range::Range;
+use crate::table_base2::TableType;
+use crate::text_index::TextIndex;
+use crate::type_data::TypeData;
+use crate::{BytesSerialize, ChunkHeader, FromReader, SuitableDataType, TableManager};
 
 // use tests::rand_string;
 
@@ -145,30 +148,57 @@ impl SuitableDataType for ImageDocument {
             _ => {}
         };
     }
+    fn index_key(&self, index: u8) -> Option<Vec<u8>> {
+        match index {
+            1 => Some(self.filename.as_buffer().to_vec()),
+            2 => Some(self.description.as_buffer().to_vec()),
+            3 => Some(self.data.as_buffer().to_vec()),
+            _ => None,
+        }
+    }
 }
 
-#[derive(Debug)]
+// Column index (matching `ImageDocument::resolve_item`/`index_key`) that `ImageDb` keeps a
+// secondary index on for exact filename lookups.
+const FILENAME_COLUMN: u8 = 1;
+
 pub struct ImageDb<Writer: Write + Seek + Read = File> {
     pub db: TableManager<ImageDocument, Writer>,
-    index: HashDb,
+    text_index: TextIndex,
     output_buf: Vec<ImageDocument>,
     output_buf_ffi: Vec<FFIImageDocument>,
 }
 
 impl ImageDb<Cursor<Vec<u8>>> {
     fn open_from_buf(b: Cursor<Vec<u8>>) -> Self {
+        let mut db = TableManager::read_from_file(b);
+        db.create_index(FILENAME_COLUMN);
         ImageDb {
-            db: TableManager::read_from_file(b),
+            db,
             ..Default::default()
         }
     }
+
+    // Reopens a table previously packed with `export_archive`, entirely in memory.
+    pub fn open_archive<R: Read + Seek>(archive: R) -> Self {
+        let mut db = TableManager::open_archive(Cursor::default(), archive);
+        db.create_index(FILENAME_COLUMN);
+        let mut s = ImageDb {
+            db,
+            ..Default::default()
+        };
+        s.load_index();
+        s
+    }
 }
 
 impl ImageDb<File> {
     pub fn open_from_file(f: File) -> ImageDb {
+        let mut db = TableManager::read_from_file(f);
+        db.create_index(FILENAME_COLUMN);
         let mut s = ImageDb {
-            db: TableManager::read_from_file(f),
-            index: HashDb::default(),
+            db,
+            text_index: TextIndex::default(),
             output_buf: Vec::<ImageDocument>::default(),
             output_buf_ffi: Vec::<FFIImageDocument>::default(),
         };
@@ -176,9 +206,11 @@ impl ImageDb<File> {
         s
     }
     pub fn new_from_file(f: File) -> ImageDb {
+        let mut db = TableManager::new(f);
+        db.create_index(FILENAME_COLUMN);
         ImageDb {
-            db: TableManager::new(f),
-            index: HashDb::default(),
+            db,
+            text_index: TextIndex::default(),
             output_buf: Vec::<ImageDocument>::default(),
             output_buf_ffi: Vec::<FFIImageDocument>::default(),
         }
@@ -263,6 +295,50 @@ pub unsafe extern "C" fn db2_drop(db: *mut ImageDb) {
     let _a = Box::from_raw(db);
 }
 
+#[no_mangle]
+pub unsafe extern "C" fn db2_export_archive(db: *mut ImageDb, path: *const c_char) {
+    let db = ImageDb::setup_pointer(db);
+    let path = CStr::from_ptr(path).to_str().unwrap();
+    let mut out = File::options()
+        .write(true)
+        .create(true)
+        .truncate(true)
+        .open(path)
+        .unwrap();
+    db.export_archive(&mut out);
+}
+
+#[no_mangle]
+pub unsafe extern "C" fn db2_open_archive(path: *const c_char) -> *mut ImageDb {
+    let path_str = CStr::from_ptr(path).to_str().unwrap();
+
+    // Read the whole `data` entry into memory *before* opening a second, truncating handle on
+    // the same path -- otherwise truncating could race a not-yet-finished read on that path.
+    let bytes = {
+        let archive_file = File::open(path_str).unwrap();
+        let mut reader = crate::archive::ArchiveReader::open(archive_file);
+        reader.read_entry("data").expect("archive missing `data` entry")
+    };
+
+    let target = File::options()
+        .write(true)
+        .truncate(true)
+        .read(true)
+        .open(path_str)
+        .unwrap();
+    let serializer = PageSerializer::from_raw_bytes(target, &bytes, None);
+    let mut db = TableManager::from_serializer(serializer);
+    db.create_index(FILENAME_COLUMN);
+    let mut s = ImageDb {
+        db,
+        text_index: TextIndex::default(),
+        output_buf: Vec::new(),
+        output_buf_ffi: Vec::new(),
+    };
+    s.load_index();
+    Box::leak(Box::new(s))
+}
+
 #[no_mangle]
 pub unsafe extern "C" fn db2_get_all(db: *mut ImageDb, mask: u8) -> FFIDocumentArray {
     let db = ImageDb::setup_pointer(db);
@@ -296,11 +372,31 @@ pub unsafe extern "C" fn db2_get_by_name<'a>(
     }
 }
 
+#[no_mangle]
+pub unsafe extern "C" fn db2_search<'a>(
+    db: *mut ImageDb,
+    query: *const c_char,
+) -> FFIDocumentArray {
+    let query = CStr::from_ptr(query).to_str().unwrap();
+    let db = ImageDb::setup_pointer(db);
+    db.search(query);
+
+    for j in &db.output_buf {
+        db.output_buf_ffi.push(j.get_ffi());
+    }
+    FFIDocumentArray {
+        ptr: db.output_buf_ffi.as_ptr(),
+        len: db.output_buf_ffi.len() as u64,
+    }
+}
+
 impl<T: Default + Write + Seek + Read> Default for ImageDb<T> {
     fn default() -> ImageDb<T> {
+        let mut db = TableManager::new(T::default());
+        db.create_index(FILENAME_COLUMN);
         ImageDb {
-            db: TableManager::new(T::default()),
-            index: HashDb::default(),
+            db,
+            text_index: TextIndex::default(),
             output_buf: Vec::default(),
             output_buf_ffi: Vec::default(),
         }
@@ -310,14 +406,31 @@ impl<T: Default + Write + Seek + Read> Default for ImageDb<T> {
 impl<W: Write + Seek + Read> ImageDb<W> {
     pub fn store(&mut self, d: ImageDocument) {
         println!("Storing {:?}", d);
-        self.index.store(d.filename.as_buffer(), d.id);
+        self.text_index.store(
+            d.id,
+            std::str::from_utf8(d.description.as_buffer()).unwrap_or(""),
+        );
         self.db.store_and_replace(d);
     }
 
     pub fn load_index(&mut self) {
-        let index_spot = self.db.serializer().get_in_all(2, None).unwrap();
-        let page = self.serializer().get_page(index_spot);
-        self.index = HashDb::from_reader_and_heap(page, &[]);
+        if let Some(&text_index_spot) = self.db.serializer().get_in_all(3, None).last() {
+            let mut page = self.serializer().get_page(text_index_spot);
+            ChunkHeader::from_reader_and_heap(&mut page, &[]);
+            self.text_index = TextIndex::from_reader_and_heap(page, &[]);
+        }
+    }
+
+    // Tokenizes `query` the same way documents were indexed, scores every matching document
+    // by TF-IDF, and returns them ranked highest-first.
+    pub fn search(&mut self, query: &str) -> &[ImageDocument] {
+        self.output_buf.clear();
+        for (id, _score) in self.text_index.search(query) {
+            if let Some(doc) = self.db.get_one(id, u8::MAX) {
+                self.output_buf.push(doc);
+            }
+        }
+        &self.output_buf
     }
 
     pub fn get(&mut self, id: u64, mask: u8) -> Option<&ImageDocument> {
@@ -332,14 +445,10 @@ impl<W: Write + Seek + Read> ImageDb<W> {
 
     pub fn get_by_name(&mut self, name: &str) -> &[ImageDocument] {
         self.output_buf.clear();
-        let mut seen = HashSet::new();
-        let pkeys = self.index.get(name.as_bytes());
+        let pkeys = self.db.get_by_index(FILENAME_COLUMN, name.as_bytes());
         for pkey in pkeys {
-            let res = self.db.get_one(pkey, u8::MAX);
-            if let Some(exists) = res {
-                if exists.filename == name && seen.insert(exists.id) {
-                    self.output_buf.push(exists);
-                }
+            if let Some(exists) = self.db.get_one(pkey, u8::MAX) {
+                self.output_buf.push(exists);
             }
         }
         &self.output_buf
@@ -348,11 +457,28 @@ impl<W: Write + Seek + Read> ImageDb<W> {
     pub fn flush_db(&mut self) {
         self.db.force_flush();
 
-        let mut buf: Cursor<Vec<u8>> = Cursor::default();
-        let ch = self.index.serialize(&mut buf);
-        buf.set_position(0);
-        let len = buf.stream_len().unwrap();
-        self.db.serializer().add_page(buf.into_inner(), len, ch);
+        let mut text_body: Vec<u8> = Vec::new();
+        self.text_index.serialize_with_heap(&mut text_body, Cursor::default());
+        let ch = ChunkHeader {
+            ty: 3,
+            tot_len: text_body.len() as u32,
+            type_size: 0,
+            tuple_count: 0,
+            heap_size: 0,
+            limits: Range::new(Some(TypeData::Int(0)), Some(TypeData::Int(0))),
+            compressed_size: 0,
+            table_type: TableType::Data,
+            bloom: BloomFilter::empty(),
+            codec: 0,
+            pkey_bloom: BloomFilter::empty(),
+            restart_encoded: false,
+            key_delta_encoded: false,
+            column_zonemaps: Vec::new(),
+        };
+        let mut page: Cursor<Vec<u8>> = Cursor::default();
+        ch.serialize_with_heap(&mut page, Cursor::default());
+        page.write_all(&text_body).unwrap();
+        self.db.serializer().add_page(page.into_inner(), ch);
 
         self.db.serializer().flush();
     }
@@ -360,6 +486,17 @@ impl<W: Write + Seek + Read> ImageDb<W> {
     pub fn serializer(&mut self) -> &mut PageSerializer<W> {
         self.db.serializer()
     }
+
+    pub fn set_codec(&mut self, codec: crate::compressor::Codec) {
+        self.db.set_codec(codec);
+    }
+
+    // Flushes then packs the whole table (rows, secondary indices, and the text search index,
+    // which all share this `ImageDb`'s underlying `PageSerializer`) into a single archive.
+    pub fn export_archive<W2: Write + Seek>(&mut self, w: &mut W2) {
+        self.flush_db();
+        self.db.export_archive(w);
+    }
 }
 
 impl ImageDb {
@@ -421,6 +558,42 @@ fn test_name_lookup() {
     assert_eq!(imdb.get_by_name("test.png"), [im2, im3]);
 }
 
+#[test]
+fn test_text_search() {
+    let mut imdb: ImageDb<Cursor<_>> = ImageDb::<Cursor<Vec<u8>>>::default();
+
+    let im1 = ImageDocument {
+        id: 0,
+        filename: "cats.jpg".into(),
+        description: "a photo of a cat that runs in the garden".into(),
+        data: "fdsa f80da8 408fdsa".into(),
+    };
+    let im2 = ImageDocument {
+        id: 1,
+        filename: "dogs.jpg".into(),
+        description: "a dog runs through the park".into(),
+        data: "fdsa f80da8 408fdsa".into(),
+    };
+    let im3 = ImageDocument {
+        id: 10,
+        filename: "mountain.jpg".into(),
+        description: "a mountain landscape with no animals".into(),
+        data: "fdsa f80da8 408fdsaf d8a0f8sa".into(),
+    };
+
+    imdb.store(im1.clone());
+    imdb.store(im2.clone());
+    imdb.store(im3.clone());
+    imdb.flush_db();
+
+    let results = imdb.search("cat").to_vec();
+    assert_eq!(results, [im1.clone()]);
+
+    let mut results: Vec<u64> = imdb.search("run").iter().map(|d| d.id).collect();
+    results.sort_unstable();
+    assert_eq!(results, [im1.id, im2.id]);
+}
+
 fn test_serialize(mut i: ImageDb<Cursor<Vec<u8>>>) -> ImageDb<Cursor<Vec<u8>>> {
     i.flush_db();
     let mut ser = i.serializer().replace_inner(Cursor::default());
@@ -462,6 +635,83 @@ fn test_long() {
     assert!(seen.iter().all(|a| *a == 1));
 }
 
+#[test]
+fn test_compressed_roundtrip() {
+    use tests::rand_string;
+
+    const TOTAL_LEN: usize = 1000;
+
+    let mut plain = ImageDb::<Cursor<Vec<u8>>>::default();
+    let mut compressed = ImageDb::<Cursor<Vec<u8>>>::default();
+    compressed.set_codec(crate::compressor::Codec::Zstd(0));
+
+    for i in 0..TOTAL_LEN {
+        let description = rand_string(200);
+        let data = rand_string(200);
+        plain.store(ImageDocument {
+            id: i as u64,
+            filename: format!("test{}", i).into(),
+            description: description.clone().into(),
+            data: data.clone().into(),
+        });
+        compressed.store(ImageDocument {
+            id: i as u64,
+            filename: format!("test{}", i).into(),
+            description: description.into(),
+            data: data.into(),
+        });
+    }
+    plain.flush_db();
+    compressed.flush_db();
+
+    for i in 0..TOTAL_LEN as u64 {
+        let a = plain.get(i, u8::MAX).unwrap().clone();
+        let b = compressed.get(i, u8::MAX).unwrap().clone();
+        assert_eq!(a, b);
+    }
+
+    let plain_len = plain.serializer().file.get_ref().len();
+    let compressed_len = compressed.serializer().file.get_ref().len();
+    assert!(
+        compressed_len < plain_len,
+        "compressed table ({} bytes) should be smaller than uncompressed ({} bytes)",
+        compressed_len,
+        plain_len
+    );
+}
+
+#[test]
+fn test_archive_roundtrip() {
+    use tests::rand_string;
+
+    const TOTAL_LEN: usize = 200;
+
+    let mut db = ImageDb::<Cursor<Vec<u8>>>::default();
+    for i in 0..TOTAL_LEN {
+        db.store(ImageDocument {
+            id: i as u64,
+            filename: format!("test{}", i).into(),
+            description: rand_string(50).into(),
+            data: rand_string(50).into(),
+        });
+    }
+
+    let mut archive: Cursor<Vec<u8>> = Cursor::default();
+    db.export_archive(&mut archive);
+
+    let mut reopened = ImageDb::open_archive(Cursor::new(archive.into_inner()));
+
+    for i in 0..TOTAL_LEN as u64 {
+        let original = db.get(i, u8::MAX).unwrap().clone();
+        let roundtripped = reopened.get(i, u8::MAX).unwrap().clone();
+        assert_eq!(original, roundtripped);
+    }
+
+    let by_name = reopened.get_by_name("test5");
+    assert_eq!(by_name.len(), 1);
+    assert_eq!(by_name[0].id, 5);
+}
+
 #[test]
 fn test_c_api() {
     use std::ffi::CString;
@@ -0,0 +1,53 @@
+// Computes the on-disk record layout (per-field offset, width, heap-indirection) for a
+// `DynamicTuple` from its field types, instead of relying on `std::mem::size_of::<T>()`
+// (which includes Rust-side padding/discriminants that don't exist in the packed format)
+// or hand-maintained constants like `Db1String::TYPE_SIZE`.
+
+use crate::db1_string::Db1String;
+use crate::type_data::Type;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct FieldLayout {
+    pub offset: u64,
+    pub width: u64,
+    // True if the field only stores a heap tag/offset/len descriptor here, with the
+    // actual payload living in the page's heap (e.g. `Db1String`).
+    pub heap_indirected: bool,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct RecordLayout {
+    pub fields: Vec<FieldLayout>,
+    // Packed: no alignment padding is inserted between fields.
+    pub total_width: u64,
+}
+
+pub fn field_width(ty: Type) -> u64 {
+    match ty {
+        Type::Int => 8,
+        Type::String => Db1String::TYPE_SIZE,
+        // A `Dictionary` symbol id -- the whole point of interning is that this is much
+        // narrower than the strings it stands in for.
+        Type::Dictionary => 4,
+        Type::Float => 8,
+        Type::Bool => 1,
+        // Stored the same way as `String` (heap-indirected via `Db1String`).
+        Type::Bytes => Db1String::TYPE_SIZE,
+        Type::Uuid => 16,
+    }
+}
+
+pub fn compute_layout(fields: &[Type]) -> RecordLayout {
+    let mut offset = 0u64;
+    let mut out = Vec::with_capacity(fields.len());
+    for &ty in fields {
+        let width = field_width(ty);
+        out.push(FieldLayout {
+            offset,
+            width,
+            heap_indirected: matches!(ty, Type::String | Type::Bytes),
+        });
+        offset += width;
+    }
+    RecordLayout { fields: out, total_width: offset }
+}
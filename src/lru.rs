@@ -0,0 +1,81 @@
+// An intrusive doubly-linked recency list over `u64` page positions: O(1) touch/remove/evict
+// without pulling in an external linked-hash-map crate (same "hand-roll the small thing" call
+// as `bloom.rs`'s FNV-1a and `compressor.rs`'s RLE stand-ins).
+use std::collections::HashMap;
+
+#[derive(Debug, Default)]
+pub struct LruList {
+    nodes: HashMap<u64, (Option<u64>, Option<u64>)>,
+    head: Option<u64>, // most recently used
+    tail: Option<u64>, // least recently used
+}
+
+impl LruList {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    // Marks `key` as most-recently-used, inserting it if it isn't already tracked.
+    pub fn touch(&mut self, key: u64) {
+        self.remove(key);
+        let old_head = self.head;
+        self.nodes.insert(key, (None, old_head));
+        if let Some(h) = old_head {
+            self.nodes.get_mut(&h).unwrap().0 = Some(key);
+        }
+        self.head = Some(key);
+        if self.tail.is_none() {
+            self.tail = Some(key);
+        }
+    }
+
+    pub fn remove(&mut self, key: u64) {
+        if let Some((prev, next)) = self.nodes.remove(&key) {
+            match prev {
+                Some(p) => self.nodes.get_mut(&p).unwrap().1 = next,
+                None => self.head = next,
+            }
+            match next {
+                Some(n) => self.nodes.get_mut(&n).unwrap().0 = prev,
+                None => self.tail = prev,
+            }
+        }
+    }
+
+    // Evicts and returns the least-recently-used key, if any.
+    pub fn pop_lru(&mut self) -> Option<u64> {
+        let tail = self.tail?;
+        self.remove(tail);
+        Some(tail)
+    }
+
+    pub fn contains(&self, key: u64) -> bool {
+        self.nodes.contains_key(&key)
+    }
+}
+
+#[test]
+fn test_lru_order() {
+    let mut lru = LruList::new();
+    lru.touch(1);
+    lru.touch(2);
+    lru.touch(3);
+    // Re-touching 1 should move it to the front, leaving 2 as the new LRU victim.
+    lru.touch(1);
+
+    assert_eq!(lru.pop_lru(), Some(2));
+    assert_eq!(lru.pop_lru(), Some(3));
+    assert_eq!(lru.pop_lru(), Some(1));
+    assert_eq!(lru.pop_lru(), None);
+}
+
+#[test]
+fn test_lru_remove() {
+    let mut lru = LruList::new();
+    lru.touch(1);
+    lru.touch(2);
+    lru.remove(1);
+    assert!(!lru.contains(1));
+    assert_eq!(lru.pop_lru(), Some(2));
+    assert_eq!(lru.pop_lru(), None);
+}
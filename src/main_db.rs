@@ -3,7 +3,8 @@
 
 
 
-use std::cmp::Ordering;
+use std::cmp::{Ordering, Reverse};
+use std::collections::BinaryHeap;
 use std::fmt::{Debug, Display, Formatter};
 use std::io::{Cursor, Read, Seek, SeekFrom, Write};
 use std::ops::RangeBounds;
@@ -39,6 +40,10 @@ impl<T: SuitableDataType, Writer: Write + Seek + Read> DbManager<T, Writer> {
     }
 
 
+    // Once this many flushed chunks have accumulated, `store` triggers a compaction so
+    // `get_in_all` doesn't have to scan an ever-lengthening list of tiny chunks.
+    pub const COMPACTION_THRESHOLD: usize = 8;
+
     // Store tuple into the database, flushing to disk if the in-memory database exceeds FLUSH_CUTOFF
     pub fn store(&mut self, t: T) {
         self.db.store(t);
@@ -47,9 +52,55 @@ impl<T: SuitableDataType, Writer: Write + Seek + Read> DbManager<T, Writer> {
             let header = self.db.get_chunk_header();
             self.previous_headers.push((self.output_stream.stream_position().unwrap(), header));
             self.db.force_flush(&mut self.output_stream);
+
+            if self.previous_headers.len() >= Self::COMPACTION_THRESHOLD {
+                self.compact_all();
+            }
         }
     }
 
+    // K-way merge every currently flushed chunk into a single larger sorted chunk, appended
+    // to the end of `output_stream`, and replace the old (offset, header) entries in
+    // `previous_headers` with the new one. `get_in_all` sees the same rows before and after --
+    // only the number of chunks it has to open and scan changes.
+    pub fn compact_all(&mut self) {
+        if self.previous_headers.len() < 2 {
+            return;
+        }
+        let old_headers = std::mem::take(&mut self.previous_headers);
+
+        let mut chunks: Vec<std::vec::IntoIter<T>> = old_headers.iter()
+            .map(|(pos, _)| {
+                self.output_stream.seek(SeekFrom::Start(*pos)).unwrap();
+                DbBase::<T>::from_reader(&mut self.output_stream).data.into_iter()
+            })
+            .collect();
+
+        // Min-heap keyed on the order-preserving byte encoding of each chunk's next row,
+        // so the merge only ever compares byte keys instead of decoding further than needed.
+        let mut fronts: Vec<Option<T>> = chunks.iter_mut().map(|it| it.next()).collect();
+        let mut heap: BinaryHeap<Reverse<([u8; 8], usize)>> = fronts
+            .iter()
+            .enumerate()
+            .filter_map(|(i, row)| row.as_ref().map(|r| Reverse((r.memcmp_key(), i))))
+            .collect();
+
+        let mut merged = DbBase::<T>::default();
+        while let Some(Reverse((_key, i))) = heap.pop() {
+            let row = fronts[i].take().unwrap();
+            merged.store(row);
+            if let Some(next) = chunks[i].next() {
+                heap.push(Reverse((next.memcmp_key(), i)));
+                fronts[i] = Some(next);
+            }
+        }
+
+        let new_pos = self.output_stream.seek(SeekFrom::End(0)).unwrap();
+        let header = merged.get_chunk_header();
+        merged.force_flush(&mut self.output_stream);
+        self.previous_headers.push((new_pos, header));
+    }
+
     // Iterate through all the previously flushed chunk headers and look for all tuples contained in range `RB`
     pub fn get_in_all<RB: RangeBounds<u64>>(&mut self, range: RB) -> Vec<T> {
         let ok_chunks: Vec<_> = self.previous_headers.iter().filter_map(|(pos, h)|
@@ -0,0 +1,175 @@
+// Zero-copy, file-backed page storage: `PageSerializer::get_page_mapped` memory-maps a page's
+// bytes straight out of the file instead of copying them into a fresh `Vec`, so a cold
+// read-only scan costs no heap allocation and the bytes stay evictable by the OS page cache the
+// same way any other memory-mapped read-only region would be.
+//
+// No `memmap2` crate is vendored in this tree, so `Mmap` below hand-declares the handful of
+// POSIX calls it needs -- the same reasoning `read_at.rs` uses to reach for
+// `std::os::unix::fs::FileExt` instead of pulling in a crate for a few syscalls. Unix only for
+// now; there's no Windows fallback (`CreateFileMapping`/`MapViewOfFile`), so `get_page_mapped`
+// simply isn't available off this platform.
+//
+// `PageBytes` is the small "owned vec or mmap slice" abstraction the zero-copy path returns:
+// read-only access goes through `Deref<Target = [u8]>` either way, and `to_mut` is the
+// copy-on-write step that upgrades a mapped view to an owned `Vec` the moment something needs to
+// mutate it (the OS mapping here is always read-only). Wiring this into `TableBase2`'s `data`/
+// `heap` fields -- so `insert_tb`/`split` transparently copy-on-write a mapped page instead of
+// every caller having to know which variant they got -- is follow-on work; this module lands the
+// storage primitive and the file-backed fetch path it needs, mirroring how `get_page_at` added a
+// `ReadAt`-based fetch path without touching `TableBase2` itself.
+
+use std::io;
+use std::ops::{Deref, Range};
+use std::sync::Arc;
+
+#[cfg(unix)]
+use std::os::raw::{c_int, c_void};
+
+#[cfg(unix)]
+extern "C" {
+    fn mmap(addr: *mut c_void, len: usize, prot: c_int, flags: c_int, fd: c_int, offset: i64) -> *mut c_void;
+    fn munmap(addr: *mut c_void, len: usize) -> c_int;
+}
+
+#[cfg(unix)]
+const PROT_READ: c_int = 1;
+#[cfg(unix)]
+const MAP_SHARED: c_int = 1;
+
+// A read-only mapping of an entire file, unmapped automatically on drop. Kept alive for as long
+// as any `PageBytes::Mapped` slice into it exists via the surrounding `Arc`.
+#[cfg(unix)]
+pub struct Mmap {
+    ptr: *const u8,
+    len: usize,
+}
+
+#[cfg(unix)]
+unsafe impl Send for Mmap {}
+#[cfg(unix)]
+unsafe impl Sync for Mmap {}
+
+#[cfg(unix)]
+impl Mmap {
+    pub fn open(file: &std::fs::File) -> io::Result<Self> {
+        use std::os::unix::io::AsRawFd;
+
+        let len = file.metadata()?.len() as usize;
+        if len == 0 {
+            // mmap(2) rejects a zero-length mapping outright -- nothing to map, so just hand
+            // back an empty one.
+            return Ok(Mmap { ptr: std::ptr::NonNull::dangling().as_ptr(), len: 0 });
+        }
+
+        let ptr = unsafe { mmap(std::ptr::null_mut(), len, PROT_READ, MAP_SHARED, file.as_raw_fd(), 0) };
+        if ptr as isize == -1 {
+            return Err(io::Error::last_os_error());
+        }
+        Ok(Mmap { ptr: ptr as *const u8, len })
+    }
+}
+
+#[cfg(unix)]
+impl Deref for Mmap {
+    type Target = [u8];
+    fn deref(&self) -> &[u8] {
+        if self.len == 0 {
+            return &[];
+        }
+        // Safe for the lifetime of `self`: the mapping stays valid until `munmap` runs in
+        // `Drop`, and this struct is never constructed over anything but a successful `mmap`.
+        unsafe { std::slice::from_raw_parts(self.ptr, self.len) }
+    }
+}
+
+#[cfg(unix)]
+impl Drop for Mmap {
+    fn drop(&mut self) {
+        if self.len != 0 {
+            unsafe {
+                munmap(self.ptr as *mut c_void, self.len);
+            }
+        }
+    }
+}
+
+// Either an owned buffer or a zero-copy view into a memory-mapped page region. Read-only access
+// goes through `Deref<Target = [u8]>` for both; a caller that needs to mutate the bytes calls
+// `to_mut` first, which copies a `Mapped` view into a fresh `Owned` one (the mapping itself is
+// never writable).
+pub enum PageBytes {
+    Owned(Vec<u8>),
+    #[cfg(unix)]
+    Mapped(Arc<Mmap>, Range<usize>),
+}
+
+impl PageBytes {
+    pub fn to_mut(&mut self) -> &mut Vec<u8> {
+        #[cfg(unix)]
+        if let PageBytes::Mapped(mmap, range) = self {
+            *self = PageBytes::Owned(mmap[range.clone()].to_vec());
+        }
+        match self {
+            PageBytes::Owned(v) => v,
+            #[cfg(unix)]
+            PageBytes::Mapped(..) => unreachable!("just upgraded to Owned above"),
+        }
+    }
+
+    pub fn into_owned(self) -> Vec<u8> {
+        match self {
+            PageBytes::Owned(v) => v,
+            #[cfg(unix)]
+            PageBytes::Mapped(mmap, range) => mmap[range].to_vec(),
+        }
+    }
+}
+
+impl Deref for PageBytes {
+    type Target = [u8];
+    fn deref(&self) -> &[u8] {
+        match self {
+            PageBytes::Owned(v) => v,
+            #[cfg(unix)]
+            PageBytes::Mapped(mmap, range) => &mmap[range.clone()],
+        }
+    }
+}
+
+impl From<Vec<u8>> for PageBytes {
+    fn from(v: Vec<u8>) -> Self {
+        PageBytes::Owned(v)
+    }
+}
+
+#[test]
+#[cfg(unix)]
+fn mapped_page_bytes_match_file_contents() {
+    let path = std::env::temp_dir().join(format!("db1_mmap_storage_test_{}.tmp", std::process::id()));
+    std::fs::write(&path, b"hello mapped world").unwrap();
+
+    let file = std::fs::File::open(&path).unwrap();
+    let mmap = Arc::new(Mmap::open(&file).unwrap());
+    let bytes = PageBytes::Mapped(mmap, 6..13);
+    assert_eq!(&*bytes, b"mapped");
+
+    std::fs::remove_file(&path).unwrap();
+}
+
+#[test]
+#[cfg(unix)]
+fn to_mut_upgrades_mapped_to_owned_without_changing_contents() {
+    let path = std::env::temp_dir().join(format!("db1_mmap_storage_test_{}.tmp", std::process::id() + 1));
+    std::fs::write(&path, b"copy on write").unwrap();
+
+    let file = std::fs::File::open(&path).unwrap();
+    let mmap = Arc::new(Mmap::open(&file).unwrap());
+    let mut bytes = PageBytes::Mapped(mmap, 0..4);
+    assert_eq!(&*bytes, b"copy");
+
+    bytes.to_mut().extend_from_slice(b"!!!");
+    assert_eq!(&*bytes, b"copy!!!");
+    assert!(matches!(bytes, PageBytes::Owned(_)));
+
+    std::fs::remove_file(&path).unwrap();
+}
@@ -1,12 +1,17 @@
 use std::collections::HashMap;
+use std::ops::Bound;
+use db1_string::Db1String;
+use dictionary::Dictionary;
 use dynamic_tuple::{DynamicTuple, RWS, TupleBuilder};
 use crate::type_data::TypeData::Null;
 use query_data::QueryData;
-use ra_ops::RANodeIterator;
+use ra_ops::{Aggregate, GroupBy, OrderBy, RANodeIterator, VecSource, WhereByIndex};
 use secondary_index::IndexDescriptor;
 use serializer::PageSerializer;
 use typed_table::TypedTable;
-use crate::parser::{CreateTable, Filter, InsertValues, Select};
+use crate::compressor::Codec;
+use crate::table_cursor::TableCursor;
+use crate::parser::{CreateTable, Delete, Filter, InsertValues, Select, SelectItem, Update};
 use crate::type_data::{Type, TypeData};
 
 enum DbOtherObjectType {
@@ -23,10 +28,16 @@ struct SecondaryIndexSchemaInfo {
 pub struct NamedTables {
     pub tables: HashMap<String, TypedTable>,
     largest_id: u64,
+    // Backs `Type::Dictionary` columns: `execute_insert` interns their string values into this
+    // in-memory table instead of writing the bytes to every row, and `execute_select` resolves
+    // the symbol ids back on the way out. Persisted as rows in the "dictionary" system table,
+    // rebuilt from it on load the same way `schema`/`index_schema` rebuild their structures.
+    dictionary: Dictionary,
 }
 
 const DATA_TABLE_ID: u64 = 2;
 const INDEX_TABLE_ID: u64 = 3;
+const DICTIONARY_TABLE_ID: u64 = 4;
 
 impl NamedTables {
     /* TODO(index-schema-storage): implement storage for secondary indices in the schema table
@@ -35,13 +46,19 @@ impl NamedTables {
             - when adding a secondary index in code, also propagate those changes to the schema table
             - abstract schema table + table info table to a separate struct
      */
+    // A composite index's `on_columns` gets one row per column here (sharing the same
+    // `table_id`/`idx_id`), tagged with its position in the key so `init_secondary_indices` can
+    // put the list back together in the right order -- a plain single-column index just writes
+    // the one row, at position 0.
     pub fn append_secondary_index(&self, ps: &mut PageSerializer<impl RWS>, idx: &IndexDescriptor, idx_id: u64, table_id: u64) {
         // Sanity check -- idx.raw_table.id_ty is the same id_ty as idx_name
 
         let index_schema = &self.tables["index_schema"];
 
-        let tb = TupleBuilder::default().add_int(table_id).add_int(idx_id).add_int(idx.on_column);
-        index_schema.store_raw(tb, ps);
+        for (position, &on_column) in idx.on_columns.iter().enumerate() {
+            let tb = TupleBuilder::default().add_int(table_id).add_int(idx_id).add_int(on_column).add_int(position as u64);
+            index_schema.store_raw(tb, ps);
+        }
     }
 
     pub fn init_secondary_indices(ps: &mut PageSerializer<impl RWS>, tables: &mut HashMap<String, TypedTable>) {
@@ -51,24 +68,64 @@ impl NamedTables {
                     Type::Int, // table ID that the index attaches to
                     Type::Int, // table ID of the index
                     Type::Int,     // on column of table
+                    Type::Int,     // this column's position within the index's key (0 for a plain single-column index)
                 ]
-            },INDEX_TABLE_ID, ps, vec!["table_name", "index_name", "on_column"]);
+            },INDEX_TABLE_ID, ps, vec!["table_name", "index_name", "on_column", "column_position"]);
 
         let mut entry = tables.entry("index_schema".to_string()).insert_entry(indices_schema);
         let indices_schema = entry.get_mut();
+
+        // A composite index is split across several rows (one per column, see
+        // `append_secondary_index`) -- regroup them by (table_id, index_id) and sort each group
+        // by `column_position` before rebuilding `on_columns`.
+        let mut by_index: HashMap<(u64, u64), Vec<(u64, u64)>> = HashMap::new();
         for tup in indices_schema.get_in_all_iter(None, u64::MAX, ps).collect(ps) {
             let table_id = tup.extract_int(0);
             let index_id = tup.extract_int(1);
             let on_column = tup.extract_int(2);
+            let position = tup.extract_int(3);
+            by_index.entry((table_id, index_id)).or_default().push((position, on_column));
+        }
+
+        for ((table_id, index_id), mut positioned_columns) in by_index {
+            positioned_columns.sort_by_key(|&(position, _)| position);
+            let on_columns = positioned_columns.into_iter().map(|(_, col)| col).collect();
 
             let index_raw_table = tables.values().find(|x| x.id_ty == index_id).unwrap().clone();
 
             tables.values_mut().find(|x| x.id_ty == table_id).unwrap().attached_indices.indices.push(IndexDescriptor {
-                on_column,
+                on_columns,
                 raw_table: index_raw_table
             });
         }
     }
+    // Rebuilds the in-memory `Dictionary` from the "dictionary" system table, the same way
+    // `init_secondary_indices` rebuilds `attached_indices` from `index_schema`. `restore` takes
+    // rows in ascending symbol-id order, and a table scan yields its last page first, so the rows
+    // are sorted by id here before restoring.
+    pub fn init_dictionary(ps: &mut PageSerializer<impl RWS>, tables: &mut HashMap<String, TypedTable>) -> Dictionary {
+        let dictionary_table = TypedTable::new(
+            DynamicTuple {
+                fields: vec![
+                    Type::Int,    // symbol id
+                    Type::String, // canonical bytes
+                ]
+            }, DICTIONARY_TABLE_ID, ps, vec!["symbol_id", "value"]);
+
+        let mut entry = tables.entry("dictionary".to_string()).insert_entry(dictionary_table);
+        let dictionary_table = entry.get_mut();
+
+        let mut dictionary = Dictionary::new();
+        let mut rows = dictionary_table.get_in_all_iter(None, u64::MAX, ps).collect(ps);
+        rows.sort_by_key(|tup| tup.extract_int(0));
+        for tup in rows {
+            let id = tup.extract_int(0) as u32;
+            let value = tup.extract_string(1);
+            dictionary.restore(id, value);
+        }
+        dictionary
+    }
+
     pub fn new(s: &mut PageSerializer<impl RWS>) -> Self {
         /*
         TODO(table-schema): abstract schema table to separate class
@@ -82,13 +139,15 @@ impl NamedTables {
             id_ty: 2,
             column_map: Default::default(),
             attached_indices: Default::default(),
+            tombstones: Default::default(),
+            codec: Codec::None,
         };
 
         let mut tables = HashMap::new();
 
         let mut entry = tables.entry("schema".to_string()).insert_entry(schema);
         let schema = entry.get_mut();
-        let mut large_id = 3;
+        let mut large_id = DICTIONARY_TABLE_ID;
 
         for tup in schema.get_in_all_iter(None, 0, s).collect(s).into_iter().rev() {
             let id = tup.extract_int(0);
@@ -103,8 +162,10 @@ impl NamedTables {
                     column_map: Default::default(),
                     id_ty: id,
                     attached_indices: Default::default(),
+                    tombstones: Default::default(),
+                    codec: Codec::None,
                 });
-            println!("Adding column {} {}", table_name, column_name);
+            log::debug!("Adding column {} {}", table_name, column_name);
             r.column_map
                 .insert(column_name.to_string(), r.ty.fields.len() as u32);
             r.ty.fields.push(column_type);
@@ -112,11 +173,12 @@ impl NamedTables {
         }
 
         Self::init_secondary_indices(s, &mut tables);
-
+        let dictionary = Self::init_dictionary(s, &mut tables);
 
         Self {
             tables,
             largest_id: large_id,
+            dictionary,
         }
     }
 
@@ -135,7 +197,7 @@ impl NamedTables {
         let schema_table = self.tables.get("schema").unwrap();
 
         for (colname, col) in &columns {
-            println!("Insert col {colname}");
+            log::debug!("Insert col {colname}");
             let tup = TupleBuilder::default()
                 .add_int(table_id)
                 .add_string(name.clone())
@@ -154,39 +216,327 @@ impl NamedTables {
         &self.tables[&name]
     }
 
+    // Transaction control, mirroring the savepoint/rollback model of transactional KV engines.
+    // The undo log itself lives on `PageSerializer` (it's pages, keyed by location, that get
+    // snapshotted and restored) -- these just forward to it, the same way every other
+    // `NamedTables` method takes `ps` as an explicit parameter rather than owning it.
+    pub fn begin(&self, ps: &mut PageSerializer<impl RWS>) {
+        ps.begin_transaction();
+    }
+
+    pub fn savepoint(&self, name: impl Into<String>, ps: &mut PageSerializer<impl RWS>) {
+        ps.savepoint(name);
+    }
+
+    pub fn rollback_to_savepoint(&self, name: &str, ps: &mut PageSerializer<impl RWS>) {
+        ps.rollback_to_savepoint(name);
+    }
+
+    pub fn rollback(&self, ps: &mut PageSerializer<impl RWS>) {
+        ps.rollback();
+    }
+
+    pub fn commit(&self, ps: &mut PageSerializer<impl RWS>) {
+        ps.commit_transaction();
+    }
+
+    // Interns `value` into the dictionary-backed column at `field_index`, persisting a new row
+    // into the "dictionary" system table when it's a value we haven't seen before, and returns
+    // the `TypeData::Symbol` to store in the row instead of the literal bytes.
+    fn intern_dictionary_field(&mut self, value: &TypeData, ps: &mut PageSerializer<impl RWS>) -> TypeData {
+        let bytes = match value {
+            TypeData::String(s) => s.as_buffer(),
+            _ => panic!("dictionary column expects a string literal, got {:?}", value),
+        };
+        let (id, is_new) = self.dictionary.intern(bytes);
+        if is_new {
+            let dictionary_table = &self.tables["dictionary"];
+            let tb = TupleBuilder::default().add_int(id as u64).add_string(String::from_utf8(bytes.to_vec()).unwrap());
+            dictionary_table.store_raw(tb, ps);
+        }
+        TypeData::Symbol(id)
+    }
+
     pub fn execute_insert(&mut self, insert: InsertValues, ps: &mut PageSerializer<impl RWS>) {
+        let dictionary_columns: Vec<usize> = self.tables[&insert.tbl_name].ty.fields.iter()
+            .enumerate()
+            .filter(|(_, ty)| **ty == Type::Dictionary)
+            .map(|(i, _)| i)
+            .collect();
+
+        let mut values = insert.values;
+        for row in &mut values {
+            for &col in &dictionary_columns {
+                row[col] = self.intern_dictionary_field(&row[col], ps);
+            }
+        }
+
         let table = self.tables.get_mut(&insert.tbl_name).unwrap();
-        for t in insert.values {
+        for t in values {
             let tuple = TupleBuilder { fields: t };
             tuple.type_check(&table.ty);
             table.store_raw(tuple, ps);
         }
     }
 
-    fn calculate_column_mask(table: &TypedTable, fields: &[String]) -> u64 {
+    fn row_matches(table: &TypedTable, filter: &[Filter], row: &TupleBuilder) -> bool {
+        filter.iter().all(|f| match f {
+            Filter::Equals(colname, v) => &row.fields[table.column_map[colname] as usize] == v,
+            Filter::LessThan(colname, v) => &row.fields[table.column_map[colname] as usize] < v,
+            Filter::GreaterThan(colname, v) => &row.fields[table.column_map[colname] as usize] > v,
+            Filter::LessEq(colname, v) => &row.fields[table.column_map[colname] as usize] <= v,
+            Filter::GreaterEq(colname, v) => &row.fields[table.column_map[colname] as usize] >= v,
+            Filter::Between(colname, lo, hi) => {
+                let val = &row.fields[table.column_map[colname] as usize];
+                val >= lo && val <= hi
+            }
+        })
+    }
+
+    // `TableBase2`/`PageSerializer` have no notion of removing a single row from a page -- the
+    // only primitive is `free_page`, which drops a whole page. So for anything other than a
+    // primary-key point delete, DELETE/UPDATE are implemented as a full-table rewrite: read every
+    // row, decide what survives (and for UPDATE, apply the new values), free every existing page
+    // for the table, then reinsert the survivors. Just as inefficient as the table-scan branches
+    // in `execute_select`, and for the same reason: there's no index telling us which pages to
+    // skip.
+    pub fn execute_delete(&mut self, delete: Delete, ps: &mut PageSerializer<impl RWS>) -> usize {
+        let table = self.tables.get_mut(&delete.tbl_name).unwrap();
+
+        // A single equality filter on the primary key is the common case, and needs no rewrite
+        // at all: `TypedTable::tombstone` just records the key as dead at a fresh generation, and
+        // every reader (`row_matches`'s callers below, `execute_select`) treats a tombstoned key
+        // as gone without ever touching the page it physically lives on. Still have to do the
+        // single-page lookup to know whether there was actually a live row to delete, rather than
+        // reporting a phantom deletion for a key that was never there.
+        if let [Filter::Equals(colname, pkey)] = delete.filter.as_slice() {
+            if table.column_map[colname] == 0 {
+                let exists = table.is_live(pkey)
+                    && !table.get_in_all_iter(Some(pkey.clone()), 1, ps).collect(ps).is_empty();
+                if exists {
+                    table.tombstone(pkey);
+                }
+                log::debug!("Deleted {} rows from {}", exists as usize, delete.tbl_name);
+                return exists as usize;
+            }
+        }
+
+        let locations = ps.get_in_all(table.id_ty, None);
+        // `to_owned` detaches any `Db1String::Ptr` from the page heap it was read from --
+        // required here since the pages are freed below, before the survivors are reinserted.
+        // Reuse `locations` (instead of going through `get_in_all_iter`, which would look them
+        // up again) for both this read and the free loop further down.
+        let all_rows: Vec<_> = TableCursor::new(locations.clone(), ps, &table.ty, None, u64::MAX)
+            .collect(ps)
+            .into_iter()
+            .map(TupleBuilder::to_owned)
+            // A row already tombstoned by a previous point delete is logically gone -- drop it
+            // here too, so rewriting the table for this (unrelated) delete doesn't resurrect it.
+            .filter(|row| table.is_live(row.first_v2()))
+            .collect();
+
+        let (to_delete, survivors): (Vec<_>, Vec<_>) = all_rows
+            .into_iter()
+            .partition(|row| Self::row_matches(table, &delete.filter, row));
+
+        for loc in locations {
+            let pkey = ps.load_page_cached(loc).limits.min.clone().unwrap();
+            ps.free_page(table.id_ty, pkey);
+        }
+        for row in survivors {
+            table.store_raw(row, ps);
+        }
+
+        log::debug!("Deleted {} rows from {}", to_delete.len(), delete.tbl_name);
+        to_delete.len()
+    }
+
+    pub fn execute_update(&mut self, update: Update, ps: &mut PageSerializer<impl RWS>) -> usize {
+        // Resolve assignment columns/types and intern any dictionary-column literals before
+        // taking a mutable borrow of the target table below.
+        let resolved: Vec<(usize, Type, TypeData)> = {
+            let table = &self.tables[&update.tbl_name];
+            update.assignments.iter()
+                .map(|(colname, value)| {
+                    let colindex = table.column_map[colname] as usize;
+                    (colindex, table.ty.fields[colindex], value.clone())
+                })
+                .collect()
+        };
+        let assignments: Vec<(usize, TypeData)> = resolved.into_iter()
+            .map(|(colindex, coltype, value)| {
+                let value = if coltype == Type::Dictionary {
+                    self.intern_dictionary_field(&value, ps)
+                } else {
+                    value
+                };
+                // Catch a column/value type mismatch here, before any page is freed below --
+                // `store_raw`'s own `type_check` assert would otherwise fire mid-rewrite, after
+                // the table's original pages are already gone.
+                assert!(
+                    matches!(
+                        (&value, coltype),
+                        (TypeData::Int(_), Type::Int)
+                            | (TypeData::String(_), Type::String)
+                            | (TypeData::Symbol(_), Type::Dictionary)
+                            | (TypeData::Float(_), Type::Float)
+                            | (TypeData::Bool(_), Type::Bool)
+                            | (TypeData::Bytes(_), Type::Bytes)
+                            | (TypeData::Uuid(_), Type::Uuid)
+                    ),
+                    "SET value for column {} does not match its declared type",
+                    colindex
+                );
+                (colindex, value)
+            })
+            .collect();
+
+        let table = self.tables.get_mut(&update.tbl_name).unwrap();
+        let locations = ps.get_in_all(table.id_ty, None);
+        // See the matching comment in `execute_delete`: detach borrowed strings before the
+        // pages backing them are freed, and reuse `locations` for both this read and the free
+        // loop further down instead of looking them up twice. Tombstoned rows are dropped here
+        // too, for the same reason as in `execute_delete`'s fallback.
+        let all_rows: Vec<_> = TableCursor::new(locations.clone(), ps, &table.ty, None, u64::MAX)
+            .collect(ps)
+            .into_iter()
+            .map(TupleBuilder::to_owned)
+            .filter(|row| table.is_live(row.first_v2()))
+            .collect();
+
+        let mut updated_count = 0;
+        let rows: Vec<TupleBuilder> = all_rows.into_iter().map(|mut row| {
+            if Self::row_matches(table, &update.filter, &row) {
+                updated_count += 1;
+                for (colindex, value) in &assignments {
+                    row.fields[*colindex] = value.clone();
+                }
+            }
+            row
+        }).collect();
+
+        for loc in locations {
+            let pkey = ps.load_page_cached(loc).limits.min.clone().unwrap();
+            ps.free_page(table.id_ty, pkey);
+        }
+        for row in rows {
+            table.store_raw(row, ps);
+        }
+
+        log::debug!("Updated {} rows in {}", updated_count, update.tbl_name);
+        updated_count
+    }
+
+    // Looks up `table`'s attached secondary index on `colindex`, if any. Returns a borrow tied to
+    // `table`'s own lifetime rather than cloning the index's `TypedTable` -- a clone would deep-copy
+    // its `tombstones` maps, which only ever grow (one entry per insert), turning what's supposed to
+    // be an O(matches) probe back into an O(inserts-ever-made) clone on every filtered query.
+    fn find_attached_index(table: &TypedTable, colindex: u32) -> Option<&TypedTable> {
+        table.attached_indices.find(colindex as u64).map(|idx| &idx.raw_table)
+    }
+
+    fn calculate_column_mask(table: &TypedTable, fields: &[SelectItem]) -> u64 {
         let mut mask = 0;
         if fields.is_empty() {
             return u64::MAX;
         }
         for f in fields {
-            if f == "*" {
+            // `COUNT(*)`'s "*" isn't a real column -- falling back to `u64::MAX` just means the
+            // scan loads every column, which is always correct (if less targeted) for a query
+            // that's about to read every row anyway.
+            let name = match f {
+                SelectItem::Column(name) => name,
+                SelectItem::Aggregate(_, name) => name,
+            };
+            if name == "*" {
                 mask = u64::MAX;
                 return mask;
             }
-            let index = table.column_map[f];
+            let index = table.column_map[name];
             assert!(index < 64);
             mask |= 1 << index;
         }
         mask
     }
 
+    fn to_ra_aggregate(agg: crate::parser::AggregateFn) -> Aggregate {
+        match agg {
+            crate::parser::AggregateFn::Count => Aggregate::Count,
+            crate::parser::AggregateFn::Sum => Aggregate::Sum,
+            crate::parser::AggregateFn::Min => Aggregate::Min,
+            crate::parser::AggregateFn::Max => Aggregate::Max,
+            crate::parser::AggregateFn::Avg => Aggregate::Avg,
+        }
+    }
+
+    // `SELECT <aggregates> ... GROUP BY <cols>` (and the `GROUP BY`-less ungrouped-aggregate
+    // case, e.g. `SELECT count(*)`): runs `rows` (already filtered and tombstone-checked by
+    // `execute_select`) through `ra_ops::GroupBy`, then projects its `[group cols..., aggregate
+    // cols...]` output back into the order the caller actually wrote `select.columns` in.
+    fn execute_group_by<W: RWS>(
+        table: &TypedTable,
+        columns: &[SelectItem],
+        group_by: &[String],
+        rows: Vec<TupleBuilder>,
+        ps: &mut PageSerializer<W>,
+    ) -> Vec<TupleBuilder> {
+        let group_cols: Vec<usize> = group_by.iter().map(|n| table.column_map[n] as usize).collect();
+        let aggregates: Vec<(Aggregate, usize)> = columns.iter().filter_map(|c| match c {
+            SelectItem::Aggregate(agg, colname) => {
+                // "*" (as in `COUNT(*)`) only makes sense for `Count`, which ignores the column
+                // it's bound to anyway -- every other aggregate needs a real numeric/orderable
+                // column to operate on.
+                let col = if colname == "*" {
+                    assert_eq!(*agg, crate::parser::AggregateFn::Count, "{:?}(*) is not a valid aggregate -- only COUNT(*) is", agg);
+                    0
+                } else {
+                    table.column_map[colname] as usize
+                };
+                Some((Self::to_ra_aggregate(*agg), col))
+            }
+            SelectItem::Column(_) => None,
+        }).collect();
+
+        let mut source = VecSource::new(rows);
+        let mut group_by_node = GroupBy::new(&mut source, group_cols, aggregates);
+        let grouped = group_by_node.collect(ps);
+
+        grouped.into_iter().map(|row| {
+            let mut agg_i = 0;
+            let fields = columns.iter().map(|item| match item {
+                SelectItem::Column(name) => {
+                    let pos = group_by.iter().position(|g| g == name)
+                        .unwrap_or_else(|| panic!("SELECT column '{}' is neither aggregated nor in GROUP BY", name));
+                    row.fields[pos].clone()
+                }
+                SelectItem::Aggregate(..) => {
+                    let idx = group_by.len() + agg_i;
+                    agg_i += 1;
+                    row.fields[idx].clone()
+                }
+            }).collect();
+            TupleBuilder { fields }
+        }).collect()
+    }
+
     pub fn execute_select<'a, W: RWS>(
         &mut self,
         select: Select,
         ps: &'a mut PageSerializer<W>,
     ) -> QueryData<'a, W> {
         let table = self.tables.get_mut(&select.tbl_name).unwrap();
-        let col_mask = Self::calculate_column_mask(table, &select.columns);
+        let requested_mask = Self::calculate_column_mask(table, &select.columns);
+        // The primary key is always loaded internally (even if the caller didn't ask for it) so
+        // `is_live` below can check it; `requested_mask` remembers whether to null it back out of
+        // the output afterwards.
+        let mut col_mask = requested_mask | 1;
+        // A `GROUP BY` column has to be loaded even when it isn't itself in `select.columns`
+        // (e.g. `SELECT count(*) FROM t GROUP BY category`).
+        if let Some(group_by) = &select.group_by {
+            for colname in group_by {
+                col_mask |= 1 << table.column_map[colname];
+            }
+        }
 
         let filter = select.filter;
 
@@ -194,32 +544,181 @@ impl NamedTables {
             Some(Filter::Equals(colname, TypeData::Int(icomp))) => {
                 match table.column_map[colname] {
                     0 => table.get_in_all_iter(Some(TypeData::Int(*icomp)), col_mask, ps).collect(ps),
-                    colindex => {
-                        println!("Warning: using inefficient table scan");
-                        let mut query_result = table.get_in_all_iter(None, col_mask, ps);
+                    colindex => match Self::find_attached_index(table, colindex) {
+                        Some(index_table) => WhereByIndex::new(index_table, table, colindex as usize, TypeData::Int(*icomp), col_mask).collect(ps),
+                        None => {
+                            // Zone maps let us skip pages whose [min, max] for this column
+                            // can't contain `icomp`, instead of loading every page.
+                            let val = TypeData::Int(*icomp);
+                            let mut query_result = table.get_in_all_by_zonemap_iter(colindex as usize, &val, col_mask, ps);
 
-                        let data = query_result.collect(ps);
-                        data.into_iter().filter(|i| match i.fields[colindex as usize] {
-                            TypeData::Int(int) => int == *icomp,
-                            _ => panic!(),
-                        }).collect()
+                            let data = query_result.collect(ps);
+                            data.into_iter().filter(|i| match i.fields[colindex as usize] {
+                                TypeData::Int(int) => int == *icomp,
+                                _ => panic!(),
+                            }).collect()
+                        }
                     }
                 }
             }
             Some(Filter::Equals(colname, TypeData::String(s))) => {
-                println!("Warning: using inefficient table scan");
-
                 let colindex = table.column_map[colname];
-                let mut qr = table.get_in_all_iter(None, col_mask, ps);
-                let qr = qr.collect(ps);
-                qr.into_iter().filter(|i| match &i.fields[colindex as usize] {
-                    TypeData::String(s1) => s1 == s,
-                    _ => panic!(),
-                }).collect()
+                match Self::find_attached_index(table, colindex) {
+                    Some(index_table) => WhereByIndex::new(index_table, table, colindex as usize, TypeData::String(s.clone()), col_mask).collect(ps),
+                    None => {
+                        let val = TypeData::String(s.clone());
+                        let mut qr = table.get_in_all_by_zonemap_iter(colindex as usize, &val, col_mask, ps);
+                        let qr = qr.collect(ps);
+                        qr.into_iter().filter(|i| match &i.fields[colindex as usize] {
+                            TypeData::String(s1) => s1 == s,
+                            _ => panic!(),
+                        }).collect()
+                    }
+                }
+            }
+            Some(Filter::LessThan(colname, comp)) => {
+                match table.column_map[colname] {
+                    0 => table.get_in_all_range_iter((Bound::Unbounded, Bound::Excluded(comp.clone())), col_mask, ps).collect(ps),
+                    colindex => {
+                        let bounds = (Bound::Unbounded, Bound::Excluded(comp.clone()));
+                        let data = table.get_in_all_by_zonemap_range_iter(colindex as usize, bounds, col_mask, ps).collect(ps);
+                        data.into_iter().filter(|i| &i.fields[colindex as usize] < comp).collect()
+                    }
+                }
+            }
+            Some(Filter::GreaterThan(colname, comp)) => {
+                match table.column_map[colname] {
+                    0 => table.get_in_all_range_iter((Bound::Excluded(comp.clone()), Bound::Unbounded), col_mask, ps).collect(ps),
+                    colindex => {
+                        let bounds = (Bound::Excluded(comp.clone()), Bound::Unbounded);
+                        let data = table.get_in_all_by_zonemap_range_iter(colindex as usize, bounds, col_mask, ps).collect(ps);
+                        data.into_iter().filter(|i| &i.fields[colindex as usize] > comp).collect()
+                    }
+                }
+            }
+            Some(Filter::LessEq(colname, comp)) => {
+                match table.column_map[colname] {
+                    0 => table.get_in_all_range_iter((Bound::Unbounded, Bound::Included(comp.clone())), col_mask, ps).collect(ps),
+                    colindex => {
+                        let bounds = (Bound::Unbounded, Bound::Included(comp.clone()));
+                        let data = table.get_in_all_by_zonemap_range_iter(colindex as usize, bounds, col_mask, ps).collect(ps);
+                        data.into_iter().filter(|i| &i.fields[colindex as usize] <= comp).collect()
+                    }
+                }
+            }
+            Some(Filter::GreaterEq(colname, comp)) => {
+                match table.column_map[colname] {
+                    0 => table.get_in_all_range_iter((Bound::Included(comp.clone()), Bound::Unbounded), col_mask, ps).collect(ps),
+                    colindex => {
+                        let bounds = (Bound::Included(comp.clone()), Bound::Unbounded);
+                        let data = table.get_in_all_by_zonemap_range_iter(colindex as usize, bounds, col_mask, ps).collect(ps);
+                        data.into_iter().filter(|i| &i.fields[colindex as usize] >= comp).collect()
+                    }
+                }
+            }
+            Some(Filter::Between(colname, lo, hi)) => {
+                match table.column_map[colname] {
+                    0 => table.get_in_all_range_iter((Bound::Included(lo.clone()), Bound::Included(hi.clone())), col_mask, ps).collect(ps),
+                    colindex => {
+                        let bounds = (Bound::Included(lo.clone()), Bound::Included(hi.clone()));
+                        let data = table.get_in_all_by_zonemap_range_iter(colindex as usize, bounds, col_mask, ps).collect(ps);
+                        data.into_iter().filter(|i| &i.fields[colindex as usize] >= lo && &i.fields[colindex as usize] <= hi).collect()
+                    }
+                }
             }
             None | Some(Filter::Equals(_, Null)) => table.get_in_all_iter(None, col_mask, ps).collect(ps),
         };
 
+        // Drop any row whose primary key is currently tombstoned -- a key deleted through the
+        // `execute_delete` fast path never had its physical row touched, so it's still sitting
+        // in storage for every branch above to find.
+        // A `GROUP BY` on the primary key needs its real value too, even when `select.columns`
+        // never names it directly (e.g. `SELECT count(*) FROM t GROUP BY id`) -- otherwise every
+        // row would collapse into one group once the pkey is nulled out below.
+        let pkey_requested = requested_mask & 1 != 0
+            || select.group_by.as_ref().map_or(false, |g| g.iter().any(|c| table.column_map[c] == 0));
+        let results: Vec<_> = results
+            .into_iter()
+            .filter(|row| table.is_live(row.first_v2()))
+            .map(|mut row| {
+                if !pkey_requested {
+                    row.fields[0] = Null;
+                }
+                row
+            })
+            .collect();
+
+        // An aggregate query (`GROUP BY`, or a bare aggregate like `SELECT count(*)` with no
+        // `GROUP BY` at all) is handled as its own pipeline stage, since its output rows no
+        // longer correspond 1:1 to the table's own columns -- but `order_by` and dictionary
+        // resolution still apply to whatever of the table's own columns survive into the
+        // projected output (a `GROUP BY` column can be sorted on or dictionary-resolved the same
+        // as in a non-aggregated select; an aggregate column can't be either).
+        let has_aggregates = select.columns.iter().any(|c| matches!(c, SelectItem::Aggregate(..)));
+        if select.group_by.is_some() || has_aggregates {
+            let group_by = select.group_by.clone().unwrap_or_default();
+            let mut results = Self::execute_group_by(table, &select.columns, &group_by, results, ps);
+
+            if let Some((colname, descending)) = &select.order_by {
+                let colindex = select.columns.iter().position(|c| matches!(c, SelectItem::Column(n) if n == colname))
+                    .unwrap_or_else(|| panic!("ORDER BY column '{}' must be a plain (non-aggregate) selected column for a GROUP BY query", colname));
+                let mut source = VecSource::new(results);
+                let mut order_by = OrderBy::new(&mut source, colindex, *descending);
+                results = order_by.collect(ps);
+            }
+
+            for (i, item) in select.columns.iter().enumerate() {
+                // `Min`/`Max` pass their source column's value straight through unchanged (see
+                // `AggState::update`), so a dictionary-encoded column needs resolving here same
+                // as a plain `Column` select -- `Count`/`Sum`/`Avg` never produce a `Symbol`.
+                let dict_colname = match item {
+                    SelectItem::Column(name) => Some(name),
+                    SelectItem::Aggregate(agg, name) if matches!(agg, crate::parser::AggregateFn::Min | crate::parser::AggregateFn::Max) => Some(name),
+                    SelectItem::Aggregate(..) => None,
+                };
+                if let Some(name) = dict_colname {
+                    let colindex = table.column_map[name] as usize;
+                    if table.ty.fields[colindex] == Type::Dictionary {
+                        for row in &mut results {
+                            if let TypeData::Symbol(id) = row.fields[i] {
+                                row.fields[i] = TypeData::String(Db1String::from(self.dictionary.resolve(id).to_vec()));
+                            }
+                        }
+                    }
+                }
+            }
+
+            return QueryData::new(results, vec![], ps);
+        }
+
+        let results = match select.order_by {
+            Some((colname, descending)) => {
+                let colindex = table.column_map[&colname] as usize;
+                let mut source = VecSource::new(results);
+                let mut order_by = OrderBy::new(&mut source, colindex, descending);
+                order_by.collect(ps)
+            }
+            None => results,
+        };
+
+        let dictionary_columns: Vec<usize> = table.ty.fields.iter()
+            .enumerate()
+            .filter(|(_, ty)| **ty == Type::Dictionary)
+            .map(|(i, _)| i)
+            .collect();
+        let results = if dictionary_columns.is_empty() {
+            results
+        } else {
+            results.into_iter().map(|mut tup| {
+                for &col in &dictionary_columns {
+                    if let TypeData::Symbol(id) = tup.fields[col] {
+                        tup.fields[col] = TypeData::String(Db1String::from(self.dictionary.resolve(id).to_vec()));
+                    }
+                }
+                tup
+            }).collect()
+        };
+
         QueryData::new(results, vec![], ps)
     }
 }
@@ -15,11 +15,12 @@ pub(crate) struct CreateTable {
 enum Token<'a> {
     Identifier(&'a str),
     String(String),
-    Empty,
     Number(u64),
+    Float(f64),
     LParens,
     RParens,
     Comma,
+    Equals,
     End,
 }
 
@@ -66,6 +67,7 @@ fn parse_user_data(str: TokenStreamRef) -> TypeData {
             let i = *i;
             TypeData::Int(i)
         }
+        Token::Float(f) => TypeData::Float(*f),
         _x => {
             panic!("Remaining: {:?}", str)
         }
@@ -106,6 +108,11 @@ fn parse_create_table(str: TokenStreamRef) -> CreateTable {
         let ty = match ty {
             "INT" | "int" => Type::Int,
             "STRING" | "string" => Type::String,
+            "DICTIONARY" | "dictionary" => Type::Dictionary,
+            "FLOAT" | "float" => Type::Float,
+            "BOOL" | "bool" => Type::Bool,
+            "BYTES" | "bytes" => Type::Bytes,
+            "UUID" | "uuid" => Type::Uuid,
             _ => panic!(),
         };
         fields.push((name.to_string(), ty));
@@ -123,66 +130,147 @@ fn parse_create_table(str: TokenStreamRef) -> CreateTable {
     }
 }
 
+// Character-by-character scanner, replacing the old `match_indices`-over-a-fixed-char-set
+// splitter: that approach couldn't express a quoted identifier containing one of the split
+// chars, or a string escape other than a literal backslash-quote. Every panic below is
+// annotated with a byte offset into `str` -- this crate has no error type anywhere
+// (nothing implements `std::error::Error`/`Display`), so a `Result` return here would be
+// out of step with the rest of the parser; a byte offset in the panic message is the
+// proportionate way to make lex errors locatable.
 fn lex(str: &str) -> TokenStream {
+    let chars: Vec<(usize, char)> = str.char_indices().collect();
     let mut tokens = Vec::new();
-    let mut prev_index = 0;
-    let mut split = Vec::new();
-    for (index, matched) in str.match_indices(&[',', ' ', '(', ')', '"', '\n', '\\']) {
-        if prev_index != index {
-            split.push(&str[prev_index..index]);
-        }
-        if !matched.is_empty() {
-            split.push(matched);
-        }
-        prev_index = index + 1;
-    }
-    if prev_index < str.len() {
-        split.push(&str[prev_index..]);
-    }
-    let mut escaped = false;
-    let mut in_string: Option<String> = None;
-    for s in split {
-        // Filter out whitespace
-        let token = match s {
-            "\\" => {
-                escaped = true;
-                assert!(in_string.is_some());
-                Token::Empty
+    let mut i = 0;
+    let byte_pos = |i: usize| chars.get(i).map_or(str.len(), |(b, _)| *b);
+
+    while i < chars.len() {
+        let (byte_idx, c) = chars[i];
+        match c {
+            ' ' | '\n' | '\r' | '\t' => {
+                i += 1;
             }
-            "\"" if !escaped => {
-                if let Some(str) = in_string.take() {
-                    Token::String(str)
-                } else {
-                    in_string = Some("".to_string());
-                    Token::Empty
+            ',' => {
+                tokens.push(Token::Comma);
+                i += 1;
+            }
+            '(' => {
+                tokens.push(Token::LParens);
+                i += 1;
+            }
+            ')' => {
+                tokens.push(Token::RParens);
+                i += 1;
+            }
+            '=' => {
+                tokens.push(Token::Equals);
+                i += 1;
+            }
+            '"' => {
+                i += 1;
+                let mut s = String::new();
+                loop {
+                    match chars.get(i) {
+                        None => panic!("Unterminated string starting at byte {}", byte_idx),
+                        Some((_, '"')) => {
+                            i += 1;
+                            break;
+                        }
+                        Some((_, '\\')) => {
+                            i += 1;
+                            match chars.get(i) {
+                                Some((_, 'n')) => s.push('\n'),
+                                Some((_, 't')) => s.push('\t'),
+                                Some((_, '"')) => s.push('"'),
+                                Some((_, '\\')) => s.push('\\'),
+                                Some((esc_byte, 'u')) => {
+                                    let esc_byte = *esc_byte;
+                                    if i + 5 > chars.len() {
+                                        panic!("Truncated \\u escape at byte {}", esc_byte);
+                                    }
+                                    let hex: String = chars[i + 1..i + 5].iter().map(|(_, c)| *c).collect();
+                                    let code = u32::from_str_radix(&hex, 16)
+                                        .unwrap_or_else(|_| panic!("Invalid \\u escape at byte {}", esc_byte));
+                                    s.push(
+                                        char::from_u32(code)
+                                            .unwrap_or_else(|| panic!("Invalid unicode escape at byte {}", esc_byte)),
+                                    );
+                                    i += 4;
+                                }
+                                Some((esc_byte, other)) => {
+                                    panic!("Unknown string escape '\\{}' at byte {}", other, esc_byte)
+                                }
+                                None => panic!("Unterminated escape at byte {}", byte_pos(i)),
+                            }
+                            i += 1;
+                        }
+                        Some((_, other)) => {
+                            s.push(*other);
+                            i += 1;
+                        }
+                    }
                 }
+                tokens.push(Token::String(s));
             }
-            x if in_string.is_some() => {
-                escaped = false;
-                in_string.as_mut().unwrap().push_str(x);
-                Token::Empty
+            '`' | '[' => {
+                let close = if c == '`' { '`' } else { ']' };
+                let start = i + 1;
+                i += 1;
+                while chars.get(i).map_or(false, |(_, c)| *c != close) {
+                    i += 1;
+                }
+                if i >= chars.len() {
+                    panic!("Unterminated quoted identifier starting at byte {}", byte_idx);
+                }
+                let end_byte = byte_pos(i);
+                tokens.push(Token::Identifier(&str[byte_pos(start)..end_byte]));
+                i += 1;
             }
-            " " | "\n" | "\r" if in_string.is_none() => continue,
-            "," => Token::Comma,
-            "(" => Token::LParens,
-            ")" => Token::RParens,
-            a => {
-                assert!(
-                    a.chars()
-                        .all(|a| a.is_alphanumeric() || a == '_' || a == '*'),
-                    "{}",
-                    a
-                );
-
-                if a.chars().all(|a| a.is_numeric()) {
-                    Token::Number(a.parse::<u64>().unwrap())
+            '-' | '0'..='9' => {
+                if c == '-' {
+                    i += 1;
+                }
+                while chars.get(i).map_or(false, |(_, c)| c.is_ascii_digit()) {
+                    i += 1;
+                }
+                let mut is_float = false;
+                if chars.get(i).map_or(false, |(_, c)| *c == '.')
+                    && chars.get(i + 1).map_or(false, |(_, c)| c.is_ascii_digit())
+                {
+                    is_float = true;
+                    i += 1;
+                    while chars.get(i).map_or(false, |(_, c)| c.is_ascii_digit()) {
+                        i += 1;
+                    }
+                }
+                let end_byte = byte_pos(i);
+                let text = &str[byte_idx..end_byte];
+                if is_float {
+                    tokens.push(Token::Float(text.parse::<f64>().unwrap()));
                 } else {
-                    Token::Identifier(a)
+                    tokens.push(Token::Number(text.parse::<u64>().unwrap_or_else(|_| {
+                        if text.starts_with('-') {
+                            panic!(
+                                "Invalid integer literal '{}' at byte {} (this crate's integers are unsigned -- there's no signed int type to parse a negative literal into)",
+                                text, byte_idx
+                            )
+                        } else {
+                            panic!("Invalid integer literal '{}' at byte {}", text, byte_idx)
+                        }
+                    })));
                 }
             }
-        };
-        if token != Token::Empty {
-            tokens.push(token);
+            a if a.is_alphanumeric() || a == '_' || a == '*' => {
+                let start = i;
+                while chars
+                    .get(i)
+                    .map_or(false, |(_, c)| c.is_alphanumeric() || *c == '_' || *c == '*')
+                {
+                    i += 1;
+                }
+                let end_byte = byte_pos(i);
+                tokens.push(Token::Identifier(&str[byte_pos(start)..end_byte]));
+            }
+            other => panic!("Unexpected character '{}' at byte {}", other, byte_idx),
         }
     }
     tokens.push(Token::End);
@@ -195,13 +283,57 @@ fn lex(str: &str) -> TokenStream {
 #[derive(Debug, PartialEq)]
 pub(crate) enum Filter {
     Equals(String, TypeData),
+    LessThan(String, TypeData),
+    GreaterThan(String, TypeData),
+    LessEq(String, TypeData),
+    GreaterEq(String, TypeData),
+    Between(String, TypeData, TypeData),
+}
+
+#[derive(Debug, PartialEq)]
+pub(crate) struct Delete {
+    pub(crate) tbl_name: String,
+    pub(crate) filter: Vec<Filter>,
+}
+
+#[derive(Debug, PartialEq)]
+pub(crate) struct Update {
+    pub(crate) tbl_name: String,
+    pub(crate) assignments: Vec<(String, TypeData)>,
+    pub(crate) filter: Vec<Filter>,
+}
+
+// Same set `ra_ops::Aggregate` implements -- kept as a separate enum here rather than reusing
+// that one, since the parser has no reason to depend on `ra_ops`.
+#[derive(Debug, PartialEq, Clone, Copy)]
+pub(crate) enum AggregateFn {
+    Count,
+    Sum,
+    Min,
+    Max,
+    Avg,
+}
+
+#[derive(Debug, PartialEq, Clone)]
+pub(crate) enum SelectItem {
+    Column(String),
+    // The aggregated column's name, or "*" for `COUNT(*)` (only meaningful for `Count`, which
+    // ignores the column's actual value anyway).
+    Aggregate(AggregateFn, String),
 }
 
 #[derive(Debug, PartialEq)]
 pub(crate) struct Select {
     pub(crate) tbl_name: String,
-    pub(crate) columns: Vec<String>,
+    pub(crate) columns: Vec<SelectItem>,
     pub(crate) filter: Vec<Filter>,
+    // Column to sort by and whether the order is descending, from a trailing
+    // `ORDER BY <col> [ASC|DESC]` clause. `ASC` is the default when unspecified.
+    pub(crate) order_by: Option<(String, bool)>,
+    // Grouping columns from a trailing `GROUP BY <col>, ...` clause. `Some(vec![])` (GROUP BY
+    // with no columns) never occurs from parsing -- an aggregate with no `GROUP BY` at all is
+    // `None`, which `NamedTables::execute_select` treats as one ungrouped group.
+    pub(crate) group_by: Option<Vec<String>>,
 }
 
 fn parse_comma_delimited_list<'a, 'b, T: 'b, F: Fn(TokenStreamRef<'a, 'b>) -> T>(
@@ -223,38 +355,146 @@ fn parse_comma_delimited_list<'a, 'b, T: 'b, F: Fn(TokenStreamRef<'a, 'b>) -> T>
 }
 
 fn parse_where(str: TokenStreamRef) -> Vec<Filter> {
-    let column_name = str.extract_identifier();
-    assert_eq!(str.extract_identifier(), "EQUALS");
-    let data = parse_user_data(str);
+    let column_name = str.extract_identifier().to_string();
+    let filter = match str.extract_identifier() {
+        "EQUALS" => Filter::Equals(column_name, parse_user_data(str)),
+        "LESS_THAN" => Filter::LessThan(column_name, parse_user_data(str)),
+        "GREATER_THAN" => Filter::GreaterThan(column_name, parse_user_data(str)),
+        "LESS_EQ" => Filter::LessEq(column_name, parse_user_data(str)),
+        "GREATER_EQ" => Filter::GreaterEq(column_name, parse_user_data(str)),
+        "BETWEEN" => {
+            let lower = parse_user_data(str);
+            assert_eq!(str.extract_identifier(), "AND");
+            let upper = parse_user_data(str);
+            Filter::Between(column_name, lower, upper)
+        }
+        op => panic!("Unknown WHERE operator {}", op),
+    };
+
+    vec![filter]
+}
 
-    vec![Filter::Equals(column_name.to_string(), data)]
+fn parse_order_by(str: TokenStreamRef) -> (String, bool) {
+    let column_name = str.extract_identifier().to_string();
+    let descending = match str.peek() {
+        Token::Identifier(s) if *s == "ASC" => {
+            str.next();
+            false
+        }
+        Token::Identifier(s) if *s == "DESC" => {
+            str.next();
+            true
+        }
+        _ => false,
+    };
+    (column_name, descending)
+}
+
+// A plain column name, or a `FUNC(col)` aggregate call -- `FUNC(*)` is only meaningful for
+// `COUNT`, which never looks at the column's value anyway.
+fn parse_select_item(str: TokenStreamRef) -> SelectItem {
+    let name = str.extract_identifier();
+    let agg = match name {
+        "COUNT" | "count" => Some(AggregateFn::Count),
+        "SUM" | "sum" => Some(AggregateFn::Sum),
+        "MIN" | "min" => Some(AggregateFn::Min),
+        "MAX" | "max" => Some(AggregateFn::Max),
+        "AVG" | "avg" => Some(AggregateFn::Avg),
+        _ => None,
+    };
+    match agg {
+        Some(agg) if str.peek() == &Token::LParens => {
+            str.next();
+            let col = str.extract_identifier().to_string();
+            str.extract(Token::RParens);
+            SelectItem::Aggregate(agg, col)
+        }
+        _ => SelectItem::Column(name.to_string()),
+    }
 }
 
 fn parse_select(str: TokenStreamRef) -> Select {
-    let columns = parse_comma_delimited_list(str, |a| a.extract_identifier());
+    let columns = parse_comma_delimited_list(str, parse_select_item);
     assert_eq!(str.extract_identifier(), "FROM");
     let tbl_name = str.extract_identifier();
 
-    let filters = match str.next() {
-        Token::Identifier(s) if *s == "WHERE" => parse_where(str),
-        _ => {
-            vec![]
+    let filters = parse_where_clause(str);
+
+    let group_by = match str.peek() {
+        Token::Identifier(s) if *s == "GROUP" => {
+            str.next();
+            assert_eq!(str.extract_identifier(), "BY");
+            Some(parse_comma_delimited_list(str, |a| a.extract_identifier().to_string()))
+        }
+        _ => None,
+    };
+
+    let order_by = match str.peek() {
+        Token::Identifier(s) if *s == "ORDER" => {
+            str.next();
+            assert_eq!(str.extract_identifier(), "BY");
+            Some(parse_order_by(str))
         }
+        _ => None,
     };
 
     Select {
         tbl_name: tbl_name.to_string(),
-        columns: columns.iter().map(|a| a.to_string()).collect(),
+        columns,
         filter: filters,
+        order_by,
+        group_by,
     }
 }
 
+fn parse_where_clause(str: TokenStreamRef) -> Vec<Filter> {
+    match str.peek() {
+        Token::Identifier(s) if *s == "WHERE" => {
+            str.next();
+            parse_where(str)
+        }
+        _ => vec![],
+    }
+}
+
+fn parse_delete(str: TokenStreamRef) -> Delete {
+    assert_eq!(str.extract_identifier(), "FROM");
+    let tbl_name = str.extract_identifier().to_string();
+    let filter = parse_where_clause(str);
+
+    Delete { tbl_name, filter }
+}
+
+fn parse_set_assignments(str: TokenStreamRef) -> Vec<(String, TypeData)> {
+    parse_comma_delimited_list(str, |str| {
+        let column_name = str.extract_identifier().to_string();
+        str.extract(Token::Equals);
+        (column_name, parse_user_data(str))
+    })
+}
+
+fn parse_update(str: TokenStreamRef) -> Update {
+    let tbl_name = str.extract_identifier().to_string();
+    assert_eq!(str.extract_identifier(), "SET");
+    let assignments = parse_set_assignments(str);
+    let filter = parse_where_clause(str);
+
+    Update { tbl_name, assignments, filter }
+}
+
 #[derive(Debug, PartialEq)]
 enum SQL {
     CreateTable(CreateTable),
     Insert(InsertValues),
     Select(Select),
+    Delete(Delete),
+    Update(Update),
     Flush,
+    Begin,
+    Savepoint(String),
+    RollbackToSavepoint(String),
+    Rollback,
+    Commit,
 }
 
 fn parse_sql(str: TokenStreamRef) -> SQL {
@@ -268,7 +508,19 @@ fn parse_sql(str: TokenStreamRef) -> SQL {
             SQL::Insert(parse_insert_values(str))
         }
         "SELECT" => SQL::Select(parse_select(str)),
+        "DELETE" => SQL::Delete(parse_delete(str)),
+        "UPDATE" => SQL::Update(parse_update(str)),
         "FLUSH" => SQL::Flush,
+        "BEGIN" => SQL::Begin,
+        "SAVEPOINT" => SQL::Savepoint(str.extract_identifier().to_string()),
+        "ROLLBACK" => match str.peek() {
+            Token::Identifier(s) if *s == "TO" => {
+                str.next();
+                SQL::RollbackToSavepoint(str.extract_identifier().to_string())
+            }
+            _ => SQL::Rollback,
+        },
+        "COMMIT" => SQL::Commit,
         _ => panic!(),
     }
 }
@@ -290,10 +542,38 @@ pub fn parse_lex_sql<'a, W: RWS>(
             table.insert_table(cr, ps);
             None
         }
+        SQL::Delete(d) => {
+            table.execute_delete(d, ps);
+            None
+        }
+        SQL::Update(u) => {
+            table.execute_update(u, ps);
+            None
+        }
         SQL::Flush => {
             ps.unload_all();
             None
         }
+        SQL::Begin => {
+            table.begin(ps);
+            None
+        }
+        SQL::Savepoint(name) => {
+            table.savepoint(name, ps);
+            None
+        }
+        SQL::RollbackToSavepoint(name) => {
+            table.rollback_to_savepoint(&name, ps);
+            None
+        }
+        SQL::Rollback => {
+            table.rollback(ps);
+            None
+        }
+        SQL::Commit => {
+            table.commit(ps);
+            None
+        }
     }
 }
 
@@ -305,6 +585,226 @@ fn select() {
     dbg!(parse_sql(&mut ts));
 }
 
+#[test]
+fn select_range() {
+    let mut ts = lex(r#"
+    SELECT col1 FROM tbl WHERE col1 BETWEEN 5 AND 10
+    "#);
+    assert_eq!(
+        parse_sql(&mut ts),
+        SQL::Select(Select {
+            tbl_name: "tbl".to_string(),
+            columns: vec![SelectItem::Column("col1".to_string())],
+            filter: vec![Filter::Between("col1".to_string(), TypeData::Int(5), TypeData::Int(10))],
+            order_by: None,
+            group_by: None,
+        })
+    );
+}
+
+#[test]
+fn select_less_eq_and_greater_eq() {
+    let mut ts = lex(r#"
+    SELECT col1 FROM tbl WHERE col1 LESS_EQ 5
+    "#);
+    assert_eq!(
+        parse_sql(&mut ts),
+        SQL::Select(Select {
+            tbl_name: "tbl".to_string(),
+            columns: vec![SelectItem::Column("col1".to_string())],
+            filter: vec![Filter::LessEq("col1".to_string(), TypeData::Int(5))],
+            order_by: None,
+            group_by: None,
+        })
+    );
+
+    let mut ts = lex(r#"
+    SELECT col1 FROM tbl WHERE col1 GREATER_EQ 5
+    "#);
+    assert_eq!(
+        parse_sql(&mut ts),
+        SQL::Select(Select {
+            tbl_name: "tbl".to_string(),
+            columns: vec![SelectItem::Column("col1".to_string())],
+            filter: vec![Filter::GreaterEq("col1".to_string(), TypeData::Int(5))],
+            order_by: None,
+            group_by: None,
+        })
+    );
+}
+
+#[test]
+fn select_order_by() {
+    let mut ts = lex(r#"
+    SELECT col1, col2 FROM tbl WHERE col1 EQUALS 5 ORDER BY col2 DESC
+    "#);
+    assert_eq!(
+        parse_sql(&mut ts),
+        SQL::Select(Select {
+            tbl_name: "tbl".to_string(),
+            columns: vec![SelectItem::Column("col1".to_string()), SelectItem::Column("col2".to_string())],
+            filter: vec![Filter::Equals("col1".to_string(), TypeData::Int(5))],
+            order_by: Some(("col2".to_string(), true)),
+            group_by: None,
+        })
+    );
+}
+
+#[test]
+fn select_order_by_no_where() {
+    let mut ts = lex(r#"
+    SELECT col1 FROM tbl ORDER BY col1
+    "#);
+    assert_eq!(
+        parse_sql(&mut ts),
+        SQL::Select(Select {
+            tbl_name: "tbl".to_string(),
+            columns: vec![SelectItem::Column("col1".to_string())],
+            filter: vec![],
+            order_by: Some(("col1".to_string(), false)),
+            group_by: None,
+        })
+    );
+}
+
+#[test]
+fn select_group_by_with_aggregate() {
+    let mut ts = lex(r#"
+    SELECT col1, count(*), sum(col2) FROM tbl GROUP BY col1
+    "#);
+    assert_eq!(
+        parse_sql(&mut ts),
+        SQL::Select(Select {
+            tbl_name: "tbl".to_string(),
+            columns: vec![
+                SelectItem::Column("col1".to_string()),
+                SelectItem::Aggregate(AggregateFn::Count, "*".to_string()),
+                SelectItem::Aggregate(AggregateFn::Sum, "col2".to_string()),
+            ],
+            filter: vec![],
+            order_by: None,
+            group_by: Some(vec!["col1".to_string()]),
+        })
+    );
+}
+
+#[test]
+fn select_ungrouped_aggregate() {
+    let mut ts = lex(r#"SELECT COUNT(*) FROM tbl"#);
+    assert_eq!(
+        parse_sql(&mut ts),
+        SQL::Select(Select {
+            tbl_name: "tbl".to_string(),
+            columns: vec![SelectItem::Aggregate(AggregateFn::Count, "*".to_string())],
+            filter: vec![],
+            order_by: None,
+            group_by: None,
+        })
+    );
+}
+
+#[test]
+fn delete_with_where() {
+    let mut ts = lex(r#"DELETE FROM tbl WHERE col1 EQUALS 5"#);
+    assert_eq!(
+        parse_sql(&mut ts),
+        SQL::Delete(Delete {
+            tbl_name: "tbl".to_string(),
+            filter: vec![Filter::Equals("col1".to_string(), TypeData::Int(5))],
+        })
+    );
+}
+
+#[test]
+fn delete_no_where() {
+    let mut ts = lex(r#"DELETE FROM tbl"#);
+    assert_eq!(
+        parse_sql(&mut ts),
+        SQL::Delete(Delete {
+            tbl_name: "tbl".to_string(),
+            filter: vec![],
+        })
+    );
+}
+
+#[test]
+fn update_with_where() {
+    let mut ts = lex(r#"UPDATE tbl SET col1=5, col2="hello" WHERE col3 EQUALS 1"#);
+    assert_eq!(
+        parse_sql(&mut ts),
+        SQL::Update(Update {
+            tbl_name: "tbl".to_string(),
+            assignments: vec![
+                ("col1".to_string(), TypeData::Int(5)),
+                ("col2".to_string(), TypeData::String("hello".into())),
+            ],
+            filter: vec![Filter::Equals("col3".to_string(), TypeData::Int(1))],
+        })
+    );
+}
+
+#[test]
+fn transaction_control_statements() {
+    let mut ts = lex(r#"BEGIN"#);
+    assert_eq!(parse_sql(&mut ts), SQL::Begin);
+
+    let mut ts = lex(r#"SAVEPOINT s1"#);
+    assert_eq!(parse_sql(&mut ts), SQL::Savepoint("s1".to_string()));
+
+    let mut ts = lex(r#"ROLLBACK TO s1"#);
+    assert_eq!(parse_sql(&mut ts), SQL::RollbackToSavepoint("s1".to_string()));
+
+    let mut ts = lex(r#"ROLLBACK"#);
+    assert_eq!(parse_sql(&mut ts), SQL::Rollback);
+
+    let mut ts = lex(r#"COMMIT"#);
+    assert_eq!(parse_sql(&mut ts), SQL::Commit);
+}
+
+#[test]
+fn lex_float_and_negative_literals() {
+    let mut ts = lex(r#"INSERT INTO tbl VALUES (3, 3.25, -1.5)"#);
+    assert_eq!(
+        parse_sql(&mut ts),
+        SQL::Insert(InsertValues {
+            values: vec![vec![TypeData::Int(3), TypeData::Float(3.25), TypeData::Float(-1.5)]],
+            tbl_name: "tbl".to_string(),
+        })
+    );
+}
+
+#[test]
+#[should_panic]
+fn lex_negative_integer_literal_panics() {
+    // `TypeData::Int` is a `u64` -- there's no signed integer type to parse a bare negative
+    // integer literal into, so this is a deliberate panic rather than silently wrapping.
+    lex(r#"-5"#);
+}
+
+#[test]
+fn lex_string_escapes() {
+    let mut ts = lex(r#""a\nb\tc\"d\\e\u00e9""#);
+    assert_eq!(
+        parse_user_data(&mut ts),
+        TypeData::String("a\nb\tc\"d\\e\u{e9}".into())
+    );
+}
+
+#[test]
+fn lex_quoted_identifiers() {
+    let mut ts = lex(r#"SELECT * FROM `my table` WHERE [col name] EQUALS 1"#);
+    assert_eq!(
+        parse_sql(&mut ts),
+        SQL::Select(Select {
+            tbl_name: "my table".to_string(),
+            columns: vec![SelectItem::Column("*".to_string())],
+            filter: vec![Filter::Equals("col name".to_string(), TypeData::Int(1))],
+            order_by: None,
+            group_by: None,
+        })
+    );
+}
+
 #[test]
 fn create_table() {
     let mut ts = lex(r#"CREATE TABLE tbl_name (
@@ -0,0 +1,393 @@
+// TCP frontend speaking (a useful subset of) the PostgreSQL v3 wire protocol, so existing
+// Postgres clients/drivers can talk to db1 instead of going through the `sql_new`/`sql_exec` C
+// FFI. Handles the startup handshake (including the SSL-negotiation probe most drivers send
+// first) and the simple query protocol ('Q'), routing query text through the same
+// `parser::parse_lex_sql` / `NamedTables` / `PageSerializer` the FFI and tests already use.
+//
+// Connections are served one at a time, reusing the single shared `NamedTables`/`PageSerializer`
+// -- nothing in this codebase's storage layer is set up for concurrent access yet, so a second
+// client simply waits its turn in `accept`.
+
+use std::io::{self, Read, Write};
+use std::net::{TcpListener, TcpStream};
+
+use dynamic_tuple::{RWS, TupleBuilder};
+use named_tables::NamedTables;
+use parser;
+use serializer::PageSerializer;
+use type_data::{Type, TypeData};
+
+const PROTOCOL_3_0: i32 = 0x0003_0000;
+const SSL_REQUEST_CODE: i32 = 80_877_103;
+const GSSENC_REQUEST_CODE: i32 = 80_877_104;
+
+// OIDs from the Postgres `pg_type` catalog (int8, text, bool, bytea, float8, uuid) that
+// `RowDescription` reports `Type`'s columns as.
+const OID_INT8: i32 = 20;
+const OID_TEXT: i32 = 25;
+const OID_BOOL: i32 = 16;
+const OID_BYTEA: i32 = 17;
+const OID_FLOAT8: i32 = 701;
+const OID_UUID: i32 = 2950;
+
+fn write_message(stream: &mut TcpStream, tag: u8, payload: &[u8]) -> io::Result<()> {
+    stream.write_all(&[tag])?;
+    stream.write_all(&((payload.len() + 4) as i32).to_be_bytes())?;
+    stream.write_all(payload)?;
+    stream.flush()
+}
+
+fn write_ready_for_query(stream: &mut TcpStream) -> io::Result<()> {
+    write_message(stream, b'Z', b"I")
+}
+
+fn write_error(stream: &mut TcpStream, message: &str) -> io::Result<()> {
+    let mut payload = Vec::new();
+    payload.push(b'S');
+    payload.extend_from_slice(b"ERROR\0");
+    payload.push(b'C');
+    payload.extend_from_slice(b"XX000\0");
+    payload.push(b'M');
+    payload.extend_from_slice(message.as_bytes());
+    payload.push(0);
+    payload.push(0);
+    write_message(stream, b'E', &payload)
+}
+
+// Reads one raw (un-prefixed by a type byte) startup-phase packet: a 4-byte length followed by
+// `length - 4` bytes of payload. Used for both `StartupMessage` and the `SSLRequest`/
+// `GSSENCRequest` probes, which share this framing.
+fn read_startup_packet(stream: &mut TcpStream) -> io::Result<Vec<u8>> {
+    let mut len_buf = [0u8; 4];
+    stream.read_exact(&mut len_buf)?;
+    let len = i32::from_be_bytes(len_buf) as usize;
+    let mut payload = vec![0u8; len - 4];
+    stream.read_exact(&mut payload)?;
+    Ok(payload)
+}
+
+fn read_tagged_message(stream: &mut TcpStream) -> io::Result<Option<(u8, Vec<u8>)>> {
+    let mut tag = [0u8; 1];
+    match stream.read_exact(&mut tag) {
+        Ok(()) => {}
+        Err(e) if e.kind() == io::ErrorKind::UnexpectedEof => return Ok(None),
+        Err(e) => return Err(e),
+    }
+    let mut len_buf = [0u8; 4];
+    stream.read_exact(&mut len_buf)?;
+    let len = i32::from_be_bytes(len_buf) as usize;
+    let mut payload = vec![0u8; len - 4];
+    stream.read_exact(&mut payload)?;
+    Ok(Some((tag[0], payload)))
+}
+
+fn cstr(buf: &[u8]) -> String {
+    let end = buf.iter().position(|&b| b == 0).unwrap_or(buf.len());
+    String::from_utf8_lossy(&buf[..end]).into_owned()
+}
+
+// Runs the startup flow: negotiates past any SSL/GSSENC probe, reads the real `StartupMessage`,
+// requests a cleartext password (accepting whatever the client sends back -- db1 has no user
+// accounts to check it against), then signals readiness. Returns `false` if the client
+// disconnected instead of sending its password; a disconnect earlier in the startup packets
+// surfaces as an `Err` from `read_startup_packet`.
+fn do_startup(stream: &mut TcpStream) -> io::Result<bool> {
+    let mut payload = read_startup_packet(stream)?;
+    loop {
+        let code = i32::from_be_bytes(payload[0..4].try_into().unwrap());
+        if code == SSL_REQUEST_CODE || code == GSSENC_REQUEST_CODE {
+            stream.write_all(b"N")?;
+            stream.flush()?;
+            payload = read_startup_packet(stream)?;
+            continue;
+        }
+        assert_eq!(code, PROTOCOL_3_0, "unsupported protocol version {}", code);
+        break;
+    }
+
+    write_message(stream, b'R', &3i32.to_be_bytes())?; // AuthenticationCleartextPassword
+    match read_tagged_message(stream)? {
+        Some((b'p', _password)) => {}
+        Some((other, _)) => panic!("expected PasswordMessage, got {:?}", other as char),
+        None => return Ok(false),
+    }
+
+    write_message(stream, b'R', &0i32.to_be_bytes())?; // AuthenticationOk
+    write_ready_for_query(stream)?;
+    Ok(true)
+}
+
+fn column_names(table: &NamedTables, tbl_name: &str) -> Vec<String> {
+    let table = &table.tables[tbl_name];
+    let mut names = vec![String::new(); table.ty.fields.len()];
+    for (name, &index) in &table.column_map {
+        names[index as usize] = name.clone();
+    }
+    names
+}
+
+fn write_row_description(stream: &mut TcpStream, names: &[String], fields: &[Type]) -> io::Result<()> {
+    let mut payload = Vec::new();
+    payload.extend_from_slice(&(names.len() as i16).to_be_bytes());
+    for (name, ty) in names.iter().zip(fields.iter()) {
+        payload.extend_from_slice(name.as_bytes());
+        payload.push(0);
+        payload.extend_from_slice(&0i32.to_be_bytes()); // table OID (none)
+        payload.extend_from_slice(&0i16.to_be_bytes()); // column attribute number (none)
+        let (type_oid, type_len) = match ty {
+            Type::Int => (OID_INT8, 8i16),
+            Type::String | Type::Dictionary => (OID_TEXT, -1i16),
+            Type::Float => (OID_FLOAT8, 8i16),
+            Type::Bool => (OID_BOOL, 1i16),
+            Type::Bytes => (OID_BYTEA, -1i16),
+            Type::Uuid => (OID_UUID, 16i16),
+        };
+        payload.extend_from_slice(&type_oid.to_be_bytes());
+        payload.extend_from_slice(&type_len.to_be_bytes());
+        payload.extend_from_slice(&(-1i32).to_be_bytes()); // type modifier (none)
+        payload.extend_from_slice(&0i16.to_be_bytes()); // format code: text
+    }
+    write_message(stream, b'T', &payload)
+}
+
+// Postgres's simple query protocol is always text format: every value is its human-readable
+// string form, length-prefixed, with -1 standing in for SQL NULL.
+fn write_data_row(stream: &mut TcpStream, tuple: &TupleBuilder) -> io::Result<()> {
+    let mut payload = Vec::new();
+    payload.extend_from_slice(&(tuple.fields.len() as i16).to_be_bytes());
+    for field in &tuple.fields {
+        match field {
+            TypeData::Null => payload.extend_from_slice(&(-1i32).to_be_bytes()),
+            TypeData::Int(i) => {
+                let text = i.to_string();
+                payload.extend_from_slice(&(text.len() as i32).to_be_bytes());
+                payload.extend_from_slice(text.as_bytes());
+            }
+            TypeData::String(s) => {
+                let text = s.as_buffer();
+                payload.extend_from_slice(&(text.len() as i32).to_be_bytes());
+                payload.extend_from_slice(text);
+            }
+            TypeData::Symbol(_) => panic!("dictionary columns must be resolved to strings before reaching the wire protocol"),
+            TypeData::Float(f) => {
+                let text = f.to_string();
+                payload.extend_from_slice(&(text.len() as i32).to_be_bytes());
+                payload.extend_from_slice(text.as_bytes());
+            }
+            TypeData::Bool(b) => {
+                let text = if *b { "t" } else { "f" };
+                payload.extend_from_slice(&(text.len() as i32).to_be_bytes());
+                payload.extend_from_slice(text.as_bytes());
+            }
+            TypeData::Bytes(s) => {
+                // Postgres's bytea text format: `\x` followed by hex digits.
+                let mut text = String::from("\\x");
+                for b in s.as_buffer() {
+                    text.push_str(&format!("{:02x}", b));
+                }
+                payload.extend_from_slice(&(text.len() as i32).to_be_bytes());
+                payload.extend_from_slice(text.as_bytes());
+            }
+            TypeData::Uuid(id) => {
+                let text = format!(
+                    "{:02x}{:02x}{:02x}{:02x}-{:02x}{:02x}-{:02x}{:02x}-{:02x}{:02x}-{:02x}{:02x}{:02x}{:02x}{:02x}{:02x}",
+                    id[0], id[1], id[2], id[3], id[4], id[5], id[6], id[7],
+                    id[8], id[9], id[10], id[11], id[12], id[13], id[14], id[15]
+                );
+                payload.extend_from_slice(&(text.len() as i32).to_be_bytes());
+                payload.extend_from_slice(text.as_bytes());
+            }
+        }
+    }
+    write_message(stream, b'D', &payload)
+}
+
+fn write_command_complete(stream: &mut TcpStream, tag: &str) -> io::Result<()> {
+    let mut payload = tag.as_bytes().to_vec();
+    payload.push(0);
+    write_message(stream, b'C', &payload)
+}
+
+// Recovers the table name after the first `FROM` for `RowDescription` without re-lexing the
+// whole query: a bare name ends at whitespace (or a trailing comma), and a quoted name -- the
+// `` `...` `` / `[...]` forms `parser.rs`'s lexer accepts, which may contain spaces -- runs to its
+// closing quote.
+fn table_name_from_query(sql: &str) -> Option<String> {
+    let mut tokens = sql.split_whitespace();
+    while let Some(tok) = tokens.next() {
+        if tok.eq_ignore_ascii_case("FROM") {
+            let rest = sql[tok.as_ptr() as usize - sql.as_ptr() as usize + tok.len()..].trim_start();
+            return match rest.chars().next()? {
+                q @ ('`' | '[') => {
+                    let close = if q == '`' { '`' } else { ']' };
+                    rest[1..].find(close).map(|end| rest[1..1 + end].to_string())
+                }
+                _ => rest.split_whitespace().next().map(|s| s.trim_matches(',').to_string()),
+            };
+        }
+    }
+    None
+}
+
+fn command_tag(sql: &str) -> String {
+    sql.split_whitespace().next().unwrap_or("").to_uppercase()
+}
+
+fn handle_query<W: RWS>(
+    stream: &mut TcpStream,
+    sql: &str,
+    table: &mut NamedTables,
+    ps: &mut PageSerializer<W>,
+) -> io::Result<()> {
+    let tag = command_tag(sql);
+    match parser::parse_lex_sql(sql, table, ps) {
+        Some(query_data) => {
+            let tbl_name = table_name_from_query(sql).expect("SELECT without FROM");
+            let names = column_names(table, &tbl_name);
+            let fields = table.tables[&tbl_name].ty.fields.clone();
+            write_row_description(stream, &names, &fields)?;
+
+            let results = query_data.results();
+            let row_count = results.len();
+            for tuple in &results {
+                write_data_row(stream, tuple)?;
+            }
+            write_command_complete(stream, &format!("SELECT {}", row_count))?;
+        }
+        None => {
+            write_command_complete(stream, &tag)?;
+        }
+    }
+    write_ready_for_query(stream)
+}
+
+fn serve_connection<W: RWS>(
+    mut stream: TcpStream,
+    table: &mut NamedTables,
+    ps: &mut PageSerializer<W>,
+) -> io::Result<()> {
+    if !do_startup(&mut stream)? {
+        return Ok(());
+    }
+
+    loop {
+        match read_tagged_message(&mut stream)? {
+            Some((b'Q', payload)) => {
+                let sql = cstr(&payload);
+                if let Err(e) = handle_query(&mut stream, &sql, table, ps) {
+                    write_error(&mut stream, &e.to_string())?;
+                    write_ready_for_query(&mut stream)?;
+                }
+            }
+            Some((b'X', _)) | None => return Ok(()),
+            Some((other, _)) => {
+                write_error(&mut stream, &format!("unsupported message type {:?}", other as char))?;
+                write_ready_for_query(&mut stream)?;
+            }
+        }
+    }
+}
+
+// Accepts connections on `addr` and serves them one at a time against the given
+// `NamedTables`/`PageSerializer`, forever (or until a connection attempt errors out).
+pub fn listen<W: RWS>(addr: &str, table: &mut NamedTables, ps: &mut PageSerializer<W>) -> io::Result<()> {
+    let listener = TcpListener::bind(addr)?;
+    for stream in listener.incoming() {
+        serve_connection(stream?, table, ps)?;
+    }
+    Ok(())
+}
+
+// Minimal client-side mirror of `write_message`/`read_tagged_message`, used only to drive
+// `serve_connection` end-to-end over a real loopback socket below.
+fn client_write_message(stream: &mut TcpStream, tag: u8, payload: &[u8]) -> io::Result<()> {
+    stream.write_all(&[tag])?;
+    stream.write_all(&((payload.len() + 4) as i32).to_be_bytes())?;
+    stream.write_all(payload)?;
+    stream.flush()
+}
+
+fn client_read_message(stream: &mut TcpStream) -> io::Result<(u8, Vec<u8>)> {
+    let mut tag = [0u8; 1];
+    stream.read_exact(&mut tag)?;
+    let mut len_buf = [0u8; 4];
+    stream.read_exact(&mut len_buf)?;
+    let len = i32::from_be_bytes(len_buf) as usize;
+    let mut payload = vec![0u8; len - 4];
+    stream.read_exact(&mut payload)?;
+    Ok((tag[0], payload))
+}
+
+// Reads messages until (and including) a `ReadyForQuery`, returning every message seen in order.
+fn client_read_until_ready(stream: &mut TcpStream) -> io::Result<Vec<(u8, Vec<u8>)>> {
+    let mut messages = Vec::new();
+    loop {
+        let (tag, payload) = client_read_message(stream)?;
+        let done = tag == b'Z';
+        messages.push((tag, payload));
+        if done {
+            return Ok(messages);
+        }
+    }
+}
+
+#[test]
+fn startup_and_simple_query_round_trip_over_loopback() {
+    use std::thread;
+
+    let listener = TcpListener::bind("127.0.0.1:0").unwrap();
+    let addr = listener.local_addr().unwrap();
+
+    let server = thread::spawn(move || {
+        let mut ps = PageSerializer::default();
+        let mut table = NamedTables::new(&mut ps);
+        let (stream, _) = listener.accept().unwrap();
+        serve_connection(stream, &mut table, &mut ps).unwrap();
+    });
+
+    let mut client = TcpStream::connect(addr).unwrap();
+
+    // StartupMessage: protocol version, no parameters.
+    let mut startup_payload = PROTOCOL_3_0.to_be_bytes().to_vec();
+    startup_payload.push(0);
+    client
+        .write_all(&((startup_payload.len() + 4) as i32).to_be_bytes())
+        .unwrap();
+    client.write_all(&startup_payload).unwrap();
+    client.flush().unwrap();
+
+    // AuthenticationCleartextPassword.
+    let (tag, _) = client_read_message(&mut client).unwrap();
+    assert_eq!(tag, b'R');
+    client_write_message(&mut client, b'p', b"ignored\0").unwrap();
+
+    // AuthenticationOk, then ReadyForQuery.
+    let (tag, _) = client_read_message(&mut client).unwrap();
+    assert_eq!(tag, b'R');
+    let (tag, _) = client_read_message(&mut client).unwrap();
+    assert_eq!(tag, b'Z');
+
+    let mut query = |sql: &str| -> Vec<(u8, Vec<u8>)> {
+        let mut payload = sql.as_bytes().to_vec();
+        payload.push(0);
+        client_write_message(&mut client, b'Q', &payload).unwrap();
+        client_read_until_ready(&mut client).unwrap()
+    };
+
+    let create_response = query("CREATE TABLE widgets ( id int, name STRING )");
+    assert_eq!(create_response.first().map(|(t, _)| *t), Some(b'C'));
+
+    let insert_response = query(r#"INSERT INTO widgets VALUES (1, "sprocket"), (2, "cog")"#);
+    assert_eq!(insert_response.first().map(|(t, _)| *t), Some(b'C'));
+
+    let select_response = query("SELECT id, name FROM widgets");
+    assert_eq!(select_response[0].0, b'T'); // RowDescription
+    let data_rows: Vec<_> = select_response.iter().filter(|(t, _)| *t == b'D').collect();
+    assert_eq!(data_rows.len(), 2);
+    let command_complete = select_response.iter().find(|(t, _)| *t == b'C').unwrap();
+    assert!(cstr(&command_complete.1).starts_with("SELECT 2"));
+
+    client_write_message(&mut client, b'X', b"").unwrap();
+    drop(client);
+    server.join().unwrap();
+}
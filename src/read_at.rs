@@ -0,0 +1,76 @@
+// Positioned-read abstraction for the read-only page access paths. Every existing read in
+// `PageSerializer` goes through `seek` + `read_exact` on `&mut self.file`, which forces
+// exclusive access and serializes all readers even though reading a page doesn't need to
+// mutate anything. `ReadAt` lets a page be fetched by absolute offset through a shared `&self`
+// handle instead, backed by `pread`-style positioned reads on real files (no shared cursor to
+// race on) and a plain slice copy for the in-memory `Cursor<Vec<u8>>` backend used in tests.
+
+use std::io::{Cursor, Error, ErrorKind, Result};
+
+pub trait ReadAt {
+    // Fills `buf` entirely with the bytes at `offset`, looping over short/interrupted reads
+    // the same way `Read::read_exact` does.
+    fn read_at(&self, buf: &mut [u8], offset: u64) -> Result<()>;
+}
+
+#[cfg(unix)]
+impl ReadAt for std::fs::File {
+    fn read_at(&self, mut buf: &mut [u8], mut offset: u64) -> Result<()> {
+        use std::os::unix::fs::FileExt;
+        while !buf.is_empty() {
+            match FileExt::read_at(self, buf, offset) {
+                Ok(0) => return Err(Error::new(ErrorKind::UnexpectedEof, "read_at: failed to fill whole buffer")),
+                Ok(n) => {
+                    buf = &mut buf[n..];
+                    offset += n as u64;
+                }
+                Err(ref e) if e.kind() == ErrorKind::Interrupted => {}
+                Err(e) => return Err(e),
+            }
+        }
+        Ok(())
+    }
+}
+
+#[cfg(windows)]
+impl ReadAt for std::fs::File {
+    fn read_at(&self, mut buf: &mut [u8], mut offset: u64) -> Result<()> {
+        use std::os::windows::fs::FileExt;
+        while !buf.is_empty() {
+            match self.seek_read(buf, offset) {
+                Ok(0) => return Err(Error::new(ErrorKind::UnexpectedEof, "read_at: failed to fill whole buffer")),
+                Ok(n) => {
+                    buf = &mut buf[n..];
+                    offset += n as u64;
+                }
+                Err(ref e) if e.kind() == ErrorKind::Interrupted => {}
+                Err(e) => return Err(e),
+            }
+        }
+        Ok(())
+    }
+}
+
+impl ReadAt for Cursor<Vec<u8>> {
+    fn read_at(&self, buf: &mut [u8], offset: u64) -> Result<()> {
+        let data = self.get_ref();
+        let start = offset as usize;
+        let end = start + buf.len();
+        if end > data.len() {
+            return Err(Error::new(ErrorKind::UnexpectedEof, "read_at: past end of buffer"));
+        }
+        buf.copy_from_slice(&data[start..end]);
+        Ok(())
+    }
+}
+
+#[test]
+fn test_read_at_cursor() {
+    let cursor = Cursor::new(vec![1u8, 2, 3, 4, 5]);
+    let mut buf = [0u8; 3];
+    ReadAt::read_at(&cursor, &mut buf, 1).unwrap();
+    assert_eq!(buf, [2, 3, 4]);
+
+    let mut too_far = [0u8; 3];
+    assert!(ReadAt::read_at(&cursor, &mut too_far, 4).is_err());
+}
@@ -1,3 +1,4 @@
+use std::ops::Bound;
 use dynamic_tuple::{RWS, TupleBuilder};
 use ra_ops::RANodeIterator;
 use serializer::PageSerializer;
@@ -8,7 +9,11 @@ use crate::typed_table::TypedTable;
 
 #[derive(Clone, Debug)]
 pub struct IndexDescriptor {
-    pub(crate) on_column: u64,
+    // The base table's column(s) this index is keyed on, in key order. A single entry is a
+    // plain single-column index (the value column holds that column's own `TypeData`); more
+    // than one makes it composite, keyed on the memcmp-encoded concatenation of every column
+    // (see `index_value`) -- so a query on just `on_columns[0]` is a valid prefix lookup.
+    pub(crate) on_columns: Vec<u64>,
     pub(crate) raw_table: TypedTable,
 }
 
@@ -35,13 +40,45 @@ pub struct SecondaryIndices {
 //     }
 // }
 
+// Shared by `append_secondary_index2` (to pick the index table's value column type) and
+// `index_value` (to build each row's value) so the two can't drift apart on what counts as
+// "composite" -- `Some` for a plain single-column index's one column, `None` for composite.
+fn as_single_column(on_columns: &[u64]) -> Option<u64> {
+    match on_columns {
+        [single] => Some(*single),
+        _ => None,
+    }
+}
+
 impl SecondaryIndices {
+    // Creates a new index table over `base_table_name`'s `on_columns` (more than one column
+    // makes it composite) and attaches it, named `"{base_table_name}_idx_{columns joined by
+    // '_'}"` -- the one public entry point for building a secondary index outside of this
+    // module (the SQL parser has no `CREATE INDEX` grammar yet, so this is currently reached
+    // only from `python-lib.rs`'s `create_index`).
+    pub fn create_index<W: RWS>(nt: &mut NamedTables, base_table_name: &str, on_columns: Vec<u64>, ps: &mut PageSerializer<W>) {
+        let idx_name = format!(
+            "{}_idx_{}",
+            base_table_name,
+            on_columns.iter().map(u64::to_string).collect::<Vec<_>>().join("_"),
+        );
+        Self::append_secondary_index2(nt, base_table_name, on_columns, idx_name, ps);
+    }
+
     // fn init<W: RWS>(ps: &mut PageSerializer<W>, nt: &mut NamedTables)
-    fn append_secondary_index2<W: RWS>(nt: &mut NamedTables, base_table_name: &str, on_column: u64, idx_name: String, ps: &mut PageSerializer<W>) {
+    fn append_secondary_index2<W: RWS>(nt: &mut NamedTables, base_table_name: &str, on_columns: Vec<u64>, idx_name: String, ps: &mut PageSerializer<W>) {
 
         let base_table = nt.tables.get_mut(base_table_name).unwrap();
 
-        let value_type = base_table.ty.fields[on_column as usize];
+        // A single-column index stores the column's own value as-is, so existing like-typed
+        // equality lookups (`find`/`query`, `ra_ops::WhereByIndex`) keep working unchanged; a
+        // composite index instead stores one `Bytes` value -- the memcmp-encoded concatenation
+        // of every indexed column (see `index_value`) -- so the index table's own pkey ordering
+        // doubles as a composite ordering usable for prefix lookups (see `query_range`).
+        let value_type = match as_single_column(&on_columns) {
+            Some(col) => base_table.ty.fields[col as usize],
+            None => Type::Bytes,
+        };
         let pkey_type = base_table.ty.fields[0];
         let cr = CreateTable {
             tbl_name: idx_name,
@@ -51,7 +88,7 @@ impl SecondaryIndices {
         let idx_table = nt.insert_table(cr, ps);
         let idx_id = idx_table.id_ty;
         let idx = IndexDescriptor {
-            on_column, raw_table: idx_table.clone()
+            on_columns, raw_table: idx_table.clone()
         };
 
         let base_table_id = nt.tables.get_mut(base_table_name).unwrap().id_ty;
@@ -64,20 +101,100 @@ impl SecondaryIndices {
         base_table.attached_indices.indices.push(idx);
     }
 
-    fn store<W: RWS>(&mut self, ps: &mut PageSerializer<W>, tuple: TupleBuilder) {
+    // Builds the value an index keyed on `on_columns` stores for `tuple`: the column's own
+    // value for a single-column index, or -- reusing `TupleBuilder::build_sortable` (the same
+    // memcmp-concatenation the external merge sort uses for its own sortable keys) -- the
+    // encoded concatenation of every indexed column, in order, for a composite one.
+    fn index_value(on_columns: &[u64], tuple: &TupleBuilder) -> TypeData {
+        match as_single_column(on_columns) {
+            Some(col) => tuple.extract(col as usize).clone(),
+            None => {
+                let key_fields = on_columns.iter().map(|&col| tuple.extract(col as usize).clone()).collect();
+                TypeData::Bytes(TupleBuilder { fields: key_fields }.build_sortable().into())
+            }
+        }
+    }
+
+    // Called from `TypedTable::store_raw` on every insert so each attached index's raw_table
+    // stays in sync with the base table -- without this, `WhereByIndex` would be probing
+    // indices that never got any (value, pkey) rows written to them. Takes `tuple` by reference
+    // since it only ever needs to read the pkey and indexed columns back out of it, and
+    // `store_raw` still needs to move the tuple itself into the base table afterwards.
+    pub(crate) fn store<W: RWS>(&self, ps: &mut PageSerializer<W>, tuple: &TupleBuilder) {
         let pkey = tuple.first_v2().clone();
         for indice in &self.indices {
-            let indexed_col = tuple.extract(indice.on_column as usize).clone();
+            let indexed_value = Self::index_value(&indice.on_columns, tuple);
             let index_tuple = TupleBuilder {
-                fields: vec![indexed_col, pkey.clone()]
+                fields: vec![indexed_value, pkey.clone()]
             };
             indice.raw_table.store_raw(index_tuple, ps);
         }
     }
 
-    fn query<W: RWS>(&self, ps: &mut PageSerializer<W>, column: u64, equal: TypeData) -> Vec<TypeData> {
-        let ind = self.indices.iter().find(|a| a.on_column == column).expect("Column is not indexed");
+    // Single source of truth for "is this column indexed by itself (not merely as a composite
+    // index's leading column)" -- shared by `query` and by `NamedTables::find_attached_index`,
+    // both of which compare a plain `TypeData` value straight against the index's own value
+    // column, so they only ever want a pure single-column index back.
+    pub(crate) fn find(&self, column: u64) -> Option<&IndexDescriptor> {
+        self.indices.iter().find(|idx| idx.on_columns.len() == 1 && idx.on_columns[0] == column)
+    }
+
+    // Like `find`, but also matches a composite index whose leading column is `column` -- the
+    // entry point for `query_range`'s prefix lookups, where a composite `(a, b)` index is usable
+    // for a query on `a` alone.
+    fn find_by_prefix(&self, column: u64) -> Option<&IndexDescriptor> {
+        self.indices.iter().find(|idx| idx.on_columns.first() == Some(&column))
+    }
+
+    // Looks up `column`'s attached index directly and returns the matching rows' primary keys --
+    // callers resolve those back into full rows themselves (see `python-lib.rs`'s `query`).
+    pub fn query<W: RWS>(&self, ps: &mut PageSerializer<W>, column: u64, equal: TypeData) -> Vec<TypeData> {
+        let ind = self.find(column).expect("Column is not indexed");
         let mut table_iter = ind.raw_table.get_in_all_iter(Some(equal), u64::MAX, ps);
         table_iter.collect(ps).into_iter().map(|a| a.extract(1).clone()).collect()
     }
+
+    // Higher than any real `TypeData::encode_memcmp()` leading tag byte (0..=7, see
+    // `type_data.rs`), so appending it to an encoded column's bytes produces a key that sorts
+    // above every real composite-index row sharing that column's value as its prefix.
+    const PREFIX_CEILING: u8 = 0xFF;
+
+    fn inclusive_bound(v: Option<TypeData>) -> Bound<TypeData> {
+        match v {
+            Some(v) => Bound::Included(v),
+            None => Bound::Unbounded,
+        }
+    }
+
+    // Range/prefix lookup on `column`'s attached index: `column` must be the index's leading
+    // column (its only column, for a single-column index, or the first of a composite one), and
+    // `lo`/`hi` bound that column's value inclusively (`None` meaning unbounded on that side) --
+    // the same semantics `TypedTable::get_in_all_range_iter` gives for the primary key, just
+    // over an index table's own key instead. For a composite index this is a genuine prefix
+    // scan: every row whose leading column falls in `[lo, hi]` matches, regardless of what its
+    // remaining columns hold.
+    pub fn query_range<W: RWS>(&self, ps: &mut PageSerializer<W>, column: u64, lo: Option<TypeData>, hi: Option<TypeData>) -> Vec<TypeData> {
+        let ind = self.find_by_prefix(column).expect("Column is not indexed");
+
+        let bounds = if ind.on_columns.len() == 1 {
+            (Self::inclusive_bound(lo), Self::inclusive_bound(hi))
+        } else {
+            // The index's value column stores the whole composite key's encoded bytes, so a
+            // bound on the leading column's own value has to become a bound on that column's
+            // *encoded prefix* of the key. `hi` additionally gets `PREFIX_CEILING` appended --
+            // without it, a plain `encode_memcmp(hi)` upper bound would be a strict prefix of
+            // (and so sort *below*) any row whose leading column is exactly `hi`, wrongly
+            // excluding it.
+            let lo = lo.map(|v| TypeData::Bytes(v.encode_memcmp().into()));
+            let hi = hi.map(|v| {
+                let mut key = v.encode_memcmp();
+                key.push(Self::PREFIX_CEILING);
+                TypeData::Bytes(key.into())
+            });
+            (Self::inclusive_bound(lo), Self::inclusive_bound(hi))
+        };
+
+        let mut table_iter = ind.raw_table.get_in_all_range_iter(bounds, u64::MAX, ps);
+        table_iter.collect(ps).into_iter().map(|a| a.extract(1).clone()).collect()
+    }
 }
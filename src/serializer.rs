@@ -1,30 +1,66 @@
 use std::collections::{BTreeMap, HashMap};
 use std::fmt::{Debug, Formatter};
-use std::io::{Cursor, Read, Seek, SeekFrom, Write};
+use std::io::{self, Cursor, Read, Seek, SeekFrom, Write};
+use std::ops::Bound;
 use std::option::Option::None;
+#[cfg(unix)]
+use std::sync::Arc;
 use std::usize;
 
 use chunk_header::{ChunkHeaderIndex, CHValue, MinKey};
 use table_base::read_to_buf;
 use table_base2::TableBase2;
 use {ChunkHeader, FromReader};
+use crate::compressor::{self, Codec};
+use crate::free_list::FreeList;
+use crate::lru::LruList;
+#[cfg(unix)]
+use crate::mmap_storage::PageBytes;
+use crate::read_at::ReadAt;
+use crate::transaction::Transaction;
 use crate::type_data::TypeData;
+use crate::wal::{crc32, Wal, WalOp, JOURNAL_REGION_SIZE};
 use serializer;
 
+// Default byte budget for the page cache when a caller doesn't size it explicitly via
+// `create`/`create_from_reader`/`smart_create` -- in the same ballpark as the old 5000-page
+// hard cap for typical page sizes.
+pub const DEFAULT_CACHE_BYTE_LIMIT: usize = 64 * 1024 * 1024;
 
 #[derive(Debug)]
 pub struct PageSerializer<W: Read + Write + Seek> {
     pub file: W,
     pub previous_headers: ChunkHeaderIndex,
     deleted: Vec<(u64, u64)>,
+    // Size-classed, coalescing free list used to reuse deleted regions for variable-length pages
+    // (`deleted` above stays as the simpler exact-size reuse mechanism for `constant_size` tables).
+    free_list: FreeList,
     pub cache: HashMap<u64, TableBase2>,
+    cache_lru: LruList,
+    cache_bytes: usize,
+    cache_byte_limit: usize,
     constant_size: Option<u64>,
+    journal: Wal,
+    // Codec applied to variable-length pages in `add_page` (see `COMPRESSED_PAGE`); `None`
+    // writes pages exactly as before. Constant-size tables never compress -- the fixed-size
+    // padding would eat the savings.
+    codec: Codec,
+    // Set for the lifetime of an open transaction (`begin_transaction` ..
+    // `commit_transaction`/`rollback`). While `Some`, `load_page_cached` snapshots a page's
+    // pre-mutation bytes into the undo log the first time it's touched, and cache eviction is
+    // paused so a dirty page's only copy can't be flushed out from under the transaction.
+    txn: Option<Transaction>,
+    // Scratch buffers for `add_page`'s page-header-plus-payload assembly, handed out over a
+    // lock-free free stack instead of a fresh `Vec::with_capacity` per flush (see
+    // `lockfree_pool::PageBufferPool`). Sized to `MAX_PAGE_SIZE` to comfortably fit any single page; `add_page` falls back to
+    // an ad-hoc allocation on the rare flush that finds every pooled buffer checked out.
+    page_buffer_pool: crate::lockfree_pool::PageBufferPool,
 }
 
 
 impl Default for PageSerializer<Cursor<Vec<u8>>> {
     fn default() -> Self {
-        Self::create(Cursor::default(), Some(serializer::MAX_PAGE_SIZE))
+        Self::create(Cursor::default(), Some(serializer::MAX_PAGE_SIZE), None)
     }
 }
 
@@ -45,6 +81,7 @@ pub struct PageData<'a, W> {
     pos: u64,
     len: u64,
     nextpos: u64,
+    is_compressed: bool,
 }
 
 impl<'a, W> Debug for PageData<'a, W> {
@@ -53,10 +90,103 @@ impl<'a, W> Debug for PageData<'a, W> {
             .field("pos", &self.pos)
             .field("len", &self.len)
             .field("nextpos", &self.nextpos)
+            .field("is_compressed", &self.is_compressed)
             .finish()
     }
 }
 
+// A page read back by `file_get_page`: either a zero-copy view straight into the file (the
+// common, uncompressed case) or an owned buffer holding a page that had to be decompressed
+// first. Both sides implement `Read` identically so callers (e.g. `TableBase2::from_reader_and_heap`)
+// don't need to know which one they got.
+pub enum PageReader<'a, W> {
+    Raw(LimitedReader<&'a mut W>),
+    Decompressed(LimitedReader<Cursor<Vec<u8>>>),
+}
+
+impl<'a, W: Read> Read for PageReader<'a, W> {
+    fn read(&mut self, buf: &mut [u8]) -> std::io::Result<usize> {
+        match self {
+            PageReader::Raw(r) => r.read(buf),
+            PageReader::Decompressed(r) => r.read(buf),
+        }
+    }
+}
+
+// Lightweight per-page metadata exposed by `PageCursor` -- enough to decide whether a page is
+// worth loading (limits, table type) without materializing a `TableBase2` from its body.
+#[derive(Debug, Clone)]
+pub struct PageMeta {
+    pub pos: u64,
+    pub len: u64,
+    pub is_compressed: bool,
+    pub header: ChunkHeader,
+}
+
+// Forward-only scanning cursor over a serializer's page stream, for callers that only need a
+// page's `ChunkHeader` (limits/`table_type`) before deciding whether to load it -- e.g. a range
+// query that wants to skip non-overlapping chunks without paying to decode every row. Skips
+// deleted pages automatically; never builds a `TableBase2`.
+pub struct PageCursor<'a, W> {
+    file: &'a mut W,
+    pos: u64,
+    peeked: Option<PageMeta>,
+}
+
+impl<'a, W: Read + Write + Seek> PageCursor<'a, W> {
+    fn advance_to_next_good(&mut self) -> Option<PageMeta> {
+        loop {
+            match PageSerializer::<W>::page_checked(&mut *self.file, Some(self.pos)) {
+                PageResult::Good(pd) => {
+                    let pos = pd.pos;
+                    let len = pd.len;
+                    let nextpos = pd.nextpos;
+                    let is_compressed = pd.is_compressed;
+                    let header = if is_compressed {
+                        let mut raw = vec![0u8; len as usize];
+                        pd.w.read_exact(&mut raw).unwrap();
+                        let decompressed = PageSerializer::<W>::decompress_page_body(&raw);
+                        Option::<ChunkHeader>::from_reader_and_heap(&mut Cursor::new(decompressed), &[])
+                    } else {
+                        let mut reader = LimitedReader::new(pd.w, len as usize);
+                        Option::<ChunkHeader>::from_reader_and_heap(&mut reader, &[])
+                    };
+                    self.pos = nextpos;
+                    if let Some(header) = header {
+                        return Some(PageMeta { pos, len, is_compressed, header });
+                    }
+                    // No chunk header at this page (shouldn't normally happen) -- keep scanning.
+                }
+                PageResult::Deleted(pd) => {
+                    self.pos = pd.nextpos;
+                }
+                PageResult::Eof => return None,
+            }
+        }
+    }
+
+    // Returns the next page's metadata without consuming it; calling this again without an
+    // intervening `skip_page`/`next_page` returns the same cached result instead of re-parsing.
+    pub fn peek_next_page(&mut self) -> Option<&PageMeta> {
+        if self.peeked.is_none() {
+            self.peeked = self.advance_to_next_good();
+        }
+        self.peeked.as_ref()
+    }
+
+    // Advances past the next page (reusing a cached peek if there is one) without reading its body.
+    pub fn skip_page(&mut self) {
+        if self.peeked.take().is_none() {
+            self.advance_to_next_good();
+        }
+    }
+
+    // Consumes and returns the next page's metadata, advancing the cursor.
+    pub fn next_page(&mut self) -> Option<PageMeta> {
+        self.peeked.take().or_else(|| self.advance_to_next_good())
+    }
+}
+
 enum PageResult<'a, W> {
     Good(PageData<'a, W>),
     Deleted(PageData<'a, W>),
@@ -80,6 +210,12 @@ impl<W: Write + Read + Seek> PageSerializer<W> {
     const WORKING_PAGE: u16 = 31920;
     const PAGEOVERHEAD: u64 = 6;
     const DELETED_PAGE: u16 = 21923;
+    // Same framing as `WORKING_PAGE`, but the payload is `[codec: u8][uncompressed_len: u32][compressed bytes]`
+    // instead of raw page content -- see `add_page`/`file_get_page`. A distinct tag keeps every
+    // existing uncompressed page (tag `WORKING_PAGE`) fully backward-compatible.
+    const COMPRESSED_PAGE: u16 = 31921;
+    // Journal region sits right after the 8-byte `CHECK_SEQ` magic; pages start after it.
+    const JOURNAL_BASE: u64 = 8;
 
 
     pub fn maximum_serialized_len(&self) -> usize {
@@ -92,17 +228,27 @@ impl<W: Write + Read + Seek> PageSerializer<W> {
     pub fn free_page(&mut self, ty: u64, pkey: TypeData) {
         // Check that page is still valid
         let p = self.previous_headers.remove(ty, pkey);
-        if let PageResult::Good(pd) = Self::page_checked(&mut self.file, Some(p)) {
+        let old_len = if let PageResult::Good(pd) = Self::page_checked(&mut self.file, Some(p)) {
             assert_eq!(pd.pos, p);
-            pd.w.seek(SeekFrom::Start(p)).unwrap();
-            pd.w.write_all(&Self::DELETED_PAGE.to_le_bytes()).unwrap();
-
-            println!("Deleting page with pos {} len {}", pd.pos, pd.len);
-
-            self.deleted.push((p, pd.len + Self::PAGEOVERHEAD));
+            pd.len
         } else {
             panic!()
-        }
+        };
+
+        // Log the free's intent (flushed, not fsynced) before flipping the tag, so a crash between the
+        // two leaves a journal entry that `replay_journal` can roll forward on reopen.
+        let tag_bytes = Self::DELETED_PAGE.to_le_bytes();
+        let (slot, record) = self.journal.begin(&mut self.file, WalOp::Free, p, old_len as u32, &tag_bytes);
+
+        self.file.seek(SeekFrom::Start(p)).unwrap();
+        self.file.write_all(&tag_bytes).unwrap();
+
+        self.journal.commit(&mut self.file, slot, &record);
+
+        println!("Deleting page with pos {} len {}", p, old_len);
+
+        self.deleted.push((p, old_len + Self::PAGEOVERHEAD));
+        self.free_list.insert(p, old_len + Self::PAGEOVERHEAD);
     }
 
     pub fn flush(&mut self) {
@@ -119,8 +265,70 @@ impl<W: Write + Read + Seek> PageSerializer<W> {
         check_seq == Self::CHECK_SEQ
     }
 
+    // Replays any journal entries left uncommitted by a crash: an `Alloc` whose page bytes
+    // don't match their logged checksum was torn, so it's wiped back to `DELETED_PAGE`; a
+    // `Free` whose tag wasn't actually flipped yet is rolled forward by flipping it now. Either
+    // way the journal entry is then marked committed so a later reopen doesn't reprocess it.
+    fn replay_journal(w: &mut W) {
+        let entries = Wal::read_all(w, Self::JOURNAL_BASE);
+        let journal = Wal::new(Self::JOURNAL_BASE);
+
+        for (slot, record) in entries {
+            if record.committed {
+                continue;
+            }
+
+            match record.op {
+                WalOp::Alloc => {
+                    let completed = (|| -> Option<bool> {
+                        w.seek(SeekFrom::Start(record.page_pos)).ok()?;
+                        let mut header = [0u8; 6];
+                        w.read_exact(&mut header).ok()?;
+                        let len = u32::from_le_bytes([header[2], header[3], header[4], header[5]]);
+                        let mut full = vec![0u8; 6 + len as usize];
+                        full[..6].copy_from_slice(&header);
+                        w.seek(SeekFrom::Start(record.page_pos)).ok()?;
+                        w.read_exact(&mut full[6..]).ok()?;
+                        Some(crc32(&full) == record.payload_checksum)
+                    })()
+                    .unwrap_or(false);
+
+                    if !completed {
+                        w.seek(SeekFrom::Start(record.page_pos)).unwrap();
+                        w.write_all(&Self::DELETED_PAGE.to_le_bytes()).unwrap();
+                        w.flush().unwrap();
+                    }
+                    journal.commit(w, slot, &record);
+                }
+                WalOp::Free => {
+                    w.seek(SeekFrom::Start(record.page_pos)).unwrap();
+                    let mut tag_buf = [0u8; 2];
+                    w.read_exact(&mut tag_buf).unwrap();
+                    if u16::from_le_bytes(tag_buf) != Self::DELETED_PAGE {
+                        w.seek(SeekFrom::Start(record.page_pos)).unwrap();
+                        w.write_all(&Self::DELETED_PAGE.to_le_bytes()).unwrap();
+                        w.flush().unwrap();
+                    }
+                    journal.commit(w, slot, &record);
+                }
+                WalOp::Write => {}
+            }
+        }
+    }
+
+    // Reads a codec id byte + uncompressed-length u32 off the front of `raw`, decompresses the
+    // rest with that codec, and sanity-checks the result against the recorded length.
+    fn decompress_page_body(raw: &[u8]) -> Vec<u8> {
+        let codec = Codec::from_u8(raw[0]);
+        let uncompressed_len = u32::from_le_bytes([raw[1], raw[2], raw[3], raw[4]]) as usize;
+        let decompressed = compressor::decompress_body(codec, &raw[5..]);
+        assert_eq!(decompressed.len(), uncompressed_len, "corrupt compressed page: length mismatch");
+        decompressed
+    }
+
     fn iter_pages(r: &mut W) -> (Vec<(u64, ChunkHeader)>, Vec<(u64, u64)>) {
         assert!(Self::check_is_valid(r));
+        r.seek(SeekFrom::Start(Self::JOURNAL_BASE + JOURNAL_REGION_SIZE)).unwrap();
         let mut v = Vec::new();
         let mut deleted = Vec::new();
 
@@ -128,8 +336,15 @@ impl<W: Write + Read + Seek> PageSerializer<W> {
             match Self::page_checked(r, None) {
                 PageResult::Good(pd) => {
                     let len = pd.len;
-                    let mut reader = LimitedReader::new(pd.w, len as usize);
-                    let ch = Option::<ChunkHeader>::from_reader_and_heap(&mut reader, &[]);
+                    let ch = if pd.is_compressed {
+                        let mut raw = vec![0u8; len as usize];
+                        pd.w.read_exact(&mut raw).unwrap();
+                        let decompressed = Self::decompress_page_body(&raw);
+                        Option::<ChunkHeader>::from_reader_and_heap(&mut Cursor::new(decompressed), &[])
+                    } else {
+                        let mut reader = LimitedReader::new(pd.w, len as usize);
+                        Option::<ChunkHeader>::from_reader_and_heap(&mut reader, &[])
+                    };
                     if let Some(ch) = ch {
                         v.push((pd.pos, ch));
                     }
@@ -148,7 +363,11 @@ impl<W: Write + Read + Seek> PageSerializer<W> {
         }
         (v, deleted)
     }
-    pub fn create_from_reader(mut w: W, constant_size: Option<u64>) -> Self {
+    // `cache_byte_limit` sizes the page cache's eviction budget (see `load_page_cached`);
+    // `None` falls back to `DEFAULT_CACHE_BYTE_LIMIT`.
+    pub fn create_from_reader(mut w: W, constant_size: Option<u64>, cache_byte_limit: Option<usize>) -> Self {
+        w.seek(SeekFrom::Start(0)).unwrap();
+        Self::replay_journal(&mut w);
         w.seek(SeekFrom::Start(0)).unwrap();
         let (pages, deleted) = PageSerializer::iter_pages(&mut w);
         let mut ch = ChunkHeaderIndex(BTreeMap::default());
@@ -158,19 +377,27 @@ impl<W: Write + Read + Seek> PageSerializer<W> {
         Self {
             file: w,
             previous_headers: ch,
+            free_list: FreeList::rebuild(deleted.iter().copied()),
             deleted,
             cache: Default::default(),
+            cache_lru: LruList::new(),
+            cache_bytes: 0,
+            cache_byte_limit: cache_byte_limit.unwrap_or(DEFAULT_CACHE_BYTE_LIMIT),
             constant_size,
+            journal: Wal::new(Self::JOURNAL_BASE),
+            codec: Codec::None,
+            txn: None,
+            page_buffer_pool: crate::lockfree_pool::PageBufferPool::new(8, MAX_PAGE_SIZE as usize),
         }
     }
     pub fn clone_headers(&self) -> ChunkHeaderIndex {
         self.previous_headers.clone()
     }
-    pub fn smart_create(mut w: W) -> Self {
+    pub fn smart_create(mut w: W, cache_byte_limit: Option<usize>) -> Self {
         if Self::check_is_valid(&mut w) {
-            Self::create_from_reader(w, None)
+            Self::create_from_reader(w, None, cache_byte_limit)
         } else {
-            Self::create(w, None)
+            Self::create(w, None, cache_byte_limit)
         }
     }
 
@@ -191,12 +418,21 @@ impl<W: Write + Read + Seek> PageSerializer<W> {
                         pos,
                         len: len as u64,
                         nextpos: pos + 2 + 4 + len as u64,
+                        is_compressed: false,
+                    }),
+                    PageSerializer::<W>::COMPRESSED_PAGE => PageResult::Good(PageData {
+                        w: file,
+                        pos,
+                        len: len as u64,
+                        nextpos: pos + 2 + 4 + len as u64,
+                        is_compressed: true,
                     }),
                     PageSerializer::<W>::DELETED_PAGE => PageResult::Deleted(PageData {
                         w: file,
                         pos,
                         len: len as u64,
                         nextpos: pos + 2 + 4 + len as u64,
+                        is_compressed: false,
                     }),
                     _ => panic!("Tried to load page incorrectly at {:?}", pos),
                 }
@@ -213,56 +449,150 @@ impl<W: Write + Read + Seek> PageSerializer<W> {
             }
         }
     }
-    pub fn create(mut w: W, constant_size: Option<u64>) -> Self {
+    pub fn create(mut w: W, constant_size: Option<u64>, cache_byte_limit: Option<usize>) -> Self {
         w.seek(SeekFrom::Start(0)).unwrap();
 
         w.write_all(&Self::CHECK_SEQ.to_le_bytes()).unwrap();
+        Wal::zero_region(&mut w, Self::JOURNAL_BASE);
         Self {
             deleted: Vec::new(),
+            free_list: FreeList::new(),
             file: w,
             previous_headers: ChunkHeaderIndex::default(),
             cache: Default::default(),
+            cache_lru: LruList::new(),
+            cache_bytes: 0,
+            cache_byte_limit: cache_byte_limit.unwrap_or(DEFAULT_CACHE_BYTE_LIMIT),
             constant_size,
+            journal: Wal::new(Self::JOURNAL_BASE),
+            codec: Codec::None,
+            txn: None,
+            page_buffer_pool: crate::lockfree_pool::PageBufferPool::new(8, MAX_PAGE_SIZE as usize),
         }
     }
-    pub fn  load_page_cached(&mut self, p: u64) -> &mut TableBase2 {
-        const BPOOLSIZE: usize = 5000;
-        if self.cache.len() >= BPOOLSIZE {
-            let mut unload_count = self.cache.len() - BPOOLSIZE;
 
-            let mut to_unload = Vec::new();
-            for k in self.cache.keys() {
-                if unload_count == 0 {
-                    break;
-                }
-                if *k != p {
-                    to_unload.push(*k);
-                    unload_count -= 1;
-                }
-            }
-            to_unload.iter().for_each(|k| self.unload_page(*k));
+    // Sets the page cache's byte budget after the fact (e.g. for callers that went through a
+    // constructor without a `cache_byte_limit` argument, like `TableManager::new`).
+    pub fn set_cache_byte_limit(&mut self, limit: usize) {
+        self.cache_byte_limit = limit;
+    }
+
+    // Sets the codec `add_page` compresses variable-length pages with from now on. Existing
+    // pages keep whatever they were written with -- the page tag (`WORKING_PAGE` vs
+    // `COMPRESSED_PAGE`) always says how to read them back, so mixing codecs across a
+    // serializer's lifetime is safe.
+    pub fn set_codec(&mut self, codec: Codec) {
+        self.codec = codec;
+    }
+
+    // Starts a transaction: until `commit_transaction`/`rollback` ends it, `load_page_cached`
+    // snapshots each page's bytes the first time it's touched and the cache stops evicting, so
+    // every page a transaction could have dirtied can be restored from the undo log.
+    pub fn begin_transaction(&mut self) {
+        assert!(self.txn.is_none(), "Transaction already in progress");
+        self.txn = Some(Transaction::new());
+    }
+
+    pub fn savepoint(&mut self, name: impl Into<String>) {
+        self.txn.as_mut().expect("No transaction in progress").savepoint(name.into());
+    }
+
+    // Restores every page touched since `name`'s savepoint was taken and forgets the log past
+    // that point; the transaction itself stays open.
+    pub fn rollback_to_savepoint(&mut self, name: &str) {
+        let restored = self.txn.as_mut().expect("No transaction in progress").rollback_to_savepoint(name);
+        self.restore_pages(restored);
+    }
+
+    // Restores every page the transaction touched and ends it.
+    pub fn rollback(&mut self) {
+        let restored = self.txn.as_mut().expect("No transaction in progress").rollback_all();
+        self.restore_pages(restored);
+        self.txn = None;
+    }
+
+    // Ends the transaction, discarding the undo log, and flushes dirty pages to disk.
+    pub fn commit_transaction(&mut self) {
+        assert!(self.txn.is_some(), "No transaction in progress");
+        self.txn = None;
+        self.unload_all();
+    }
+
+    // Reconstructs each `(location, pre-image bytes)` entry via `TableBase2`'s normal on-disk
+    // format and drops it back into the cache, overwriting whatever's there now. If the same
+    // location appears more than once (it was touched again in a later segment that's also
+    // being rolled back), the entry closest to the target savepoint -- i.e. the first one in
+    // this oldest-first list -- is the one that should win.
+    fn restore_pages(&mut self, entries: Vec<(u64, Vec<u8>)>) {
+        let mut winners: HashMap<u64, Vec<u8>> = HashMap::new();
+        for (location, bytes) in entries {
+            winners.entry(location).or_insert(bytes);
         }
+        for (location, bytes) in winners {
+            let mut page = TableBase2::from_reader_and_heap(Cursor::new(bytes), &[]);
+            page.loaded_location = Some(location);
+            let new_len = page.serialized_len();
+            let old_len = self.cache.get(&location).map_or(0, TableBase2::serialized_len);
+            self.cache_bytes = self.cache_bytes.saturating_sub(old_len) + new_len;
+            self.cache.insert(location, page);
+            self.cache_lru.touch(location);
+        }
+    }
 
-        let file = &mut self.file;
-        let table = self.cache.entry(p).or_insert_with(|| {
+    // Loads page `p` into the cache (a no-op if already cached, just marking it
+    // most-recently-used). Tracks the cache's footprint in bytes rather than page count, and
+    // evicts least-recently-used pages (flushing them first if dirty) until the incoming
+    // page fits the byte budget -- `p` itself is never a candidate since it isn't cached yet.
+    // Eviction is paused for the duration of an open transaction (see `begin_transaction`), so
+    // a page's only copy can't be flushed to disk -- and its undo pre-image lost -- mid-transaction.
+    pub fn load_page_cached(&mut self, p: u64) -> &mut TableBase2 {
+        if !self.cache.contains_key(&p) {
+            let file = &mut self.file;
             let page_reader = Self::file_get_page(file, p);
             let mut page = TableBase2::from_reader_and_heap(page_reader, &[]);
             page.loaded_location = Some(p);
-            page
-        });
-        table
+            let incoming_len = page.serialized_len();
+
+            while !self.cache.is_empty() && self.txn.is_none() && self.cache_bytes + incoming_len > self.cache_byte_limit {
+                match self.cache_lru.pop_lru() {
+                    Some(victim) => self.unload_page(victim),
+                    None => break,
+                }
+            }
+
+            self.cache_bytes += incoming_len;
+            self.cache.insert(p, page);
+        }
+        self.cache_lru.touch(p);
+
+        if let Some(txn) = self.txn.as_mut() {
+            if let Some(page) = self.cache.get_mut(&p) {
+                txn.capture(p, || page.snapshot());
+            }
+        }
+
+        self.cache.get_mut(&p).unwrap()
     }
-    pub fn file_get_page(file: &mut W, position: u64) -> LimitedReader<&mut W> {
+    pub fn file_get_page(file: &mut W, position: u64) -> PageReader<'_, W> {
         match PageSerializer::<W>::page_checked(file, Some(position)) {
             PageResult::Good(pd) => {
-                let size = pd.len;
+                if pd.is_compressed {
+                    let mut raw = vec![0u8; pd.len as usize];
+                    pd.w.read_exact(&mut raw).unwrap();
+                    let decompressed = Self::decompress_page_body(&raw);
+                    let len = decompressed.len();
+                    log::debug!("Yielding compressed page {} ({} compressed, {} raw)", position, pd.len, len);
+                    PageReader::Decompressed(LimitedReader::new(Cursor::new(decompressed), len))
+                } else {
+                    let size = pd.len;
+
+                    if size == 0 {
+                        println!("Tried to load zero-sized page")
+                    }
 
-                if size == 0 {
-                    println!("Tried to load zero-sized page")
+                    log::debug!("Yielding page {} {}", position, pd.len);
+                    PageReader::Raw(LimitedReader::new(pd.w, pd.len as usize))
                 }
-
-                log::debug!("Yielding page {} {}", position, pd.len);
-                LimitedReader::new(pd.w, pd.len as usize)
             }
             x => {
                 panic!("Got page {:?}", x)
@@ -280,6 +610,8 @@ impl<W: Write + Read + Seek> PageSerializer<W> {
     }
     fn unload_page(&mut self, p: u64) {
         let mut page = self.cache.remove(&p).unwrap();
+        self.cache_bytes = self.cache_bytes.saturating_sub(page.serialized_len());
+        self.cache_lru.remove(p);
         if page.dirty {
             page.force_flush(self);
         }
@@ -306,6 +638,39 @@ impl<W: Write + Read + Seek> PageSerializer<W> {
 
         left.map(|a| a.1.location)
     }
+
+    // The raw on-disk page stream (`CHECK_SEQ` magic followed by every page, including
+    // deleted ones) -- exactly what `create_from_reader`/`smart_create` need to rebuild a
+    // `PageSerializer` from scratch. Used to pack a table into a `crate::archive` entry.
+    pub fn raw_bytes(&mut self) -> Vec<u8> {
+        self.flush();
+        self.file.seek(SeekFrom::Start(0)).unwrap();
+        let mut buf = Vec::new();
+        self.file.read_to_end(&mut buf).unwrap();
+        buf
+    }
+
+    // Packs this table's raw page stream into a single-entry archive.
+    pub fn export_archive<W2: Write + Seek>(&mut self, w: &mut W2) {
+        let bytes = self.raw_bytes();
+        crate::archive::write_archive(w, &[("data", bytes.as_slice())]);
+    }
+
+    // Rebuilds a `PageSerializer` by writing a previously-exported page stream into `target`
+    // and reconstructing `previous_headers`/`deleted` from it, same as `create_from_reader`.
+    pub fn from_raw_bytes(mut target: W, page_stream_bytes: &[u8], constant_size: Option<u64>) -> Self {
+        target.seek(SeekFrom::Start(0)).unwrap();
+        target.write_all(page_stream_bytes).unwrap();
+        target.flush().unwrap();
+        Self::create_from_reader(target, constant_size, None)
+    }
+
+    // Reopens a table previously packed with `export_archive`, writing its pages into `target`.
+    pub fn open_archive<R: Read + Seek>(target: W, archive: R, constant_size: Option<u64>) -> Self {
+        let mut reader = crate::archive::ArchiveReader::open(archive);
+        let bytes = reader.read_entry("data").expect("archive missing `data` entry");
+        Self::from_raw_bytes(target, &bytes, constant_size)
+    }
 }
 
 impl<W: Read> Read for LimitedReader<W> {
@@ -318,6 +683,115 @@ impl<W: Read> Read for LimitedReader<W> {
     }
 }
 
+impl<W: Write + Read + Seek + ReadAt> PageSerializer<W> {
+    // Fetches the page at `position` by absolute offset through `ReadAt` rather than seeking
+    // `self.file`'s shared cursor, so callers holding only a `&self` (e.g. concurrent readers
+    // alongside `get_in_all`) can read pages without taking the `&mut self` write lock that
+    // `get_page`/`file_get_page` require. Always goes to disk -- unlike `get_page` it doesn't
+    // consult or populate `self.cache`. Panics if the page at `position` has been freed.
+    pub fn get_page_at(&self, position: u64) -> Vec<u8> {
+        let mut header = [0u8; Self::PAGEOVERHEAD as usize];
+        self.file.read_at(&mut header, position).unwrap();
+        let tag = u16::from_le_bytes([header[0], header[1]]);
+        let len = u32::from_le_bytes([header[2], header[3], header[4], header[5]]) as usize;
+
+        let mut raw = vec![0u8; len];
+        self.file.read_at(&mut raw, position + Self::PAGEOVERHEAD).unwrap();
+
+        match tag {
+            Self::WORKING_PAGE => raw,
+            Self::COMPRESSED_PAGE => Self::decompress_page_body(&raw),
+            Self::DELETED_PAGE => panic!("get_page_at: page at {} has been freed", position),
+            _ => panic!("get_page_at: bad page tag at {}", position),
+        }
+    }
+}
+
+#[cfg(unix)]
+impl PageSerializer<std::fs::File> {
+    // Real load-path counterpart to `PageSerializer::load_page_cached`, specialized to
+    // `std::fs::File` so it can route through `get_page_mapped`'s zero-copy fetch instead of
+    // `file_get_page`'s owned copy. `load_page_cached` itself stays generic over `impl RWS` --
+    // every call site in `typed_table.rs`/`table_cursor.rs`/`named_tables.rs` reaches it that
+    // way -- so widening its bound to require `get_page_mapped` would ripple that requirement
+    // through every one of those generic callers (including backends like `Cursor<Vec<u8>>`
+    // that have no file to map) rather than staying a scoped, file-specific optimization.
+    //
+    // Nothing in this tree calls it: the file-backed `PageSerializer`s (built by `typed_row.rs`'s
+    // `typed_table_new` and `index.rs` through `TableManager`) load pages through the generic
+    // `load_page_cached` like every other backend.
+    pub fn load_page_cached_mapped(&mut self, p: u64) -> &mut TableBase2 {
+        if !self.cache.contains_key(&p) {
+            let bytes = self.get_page_mapped(p).unwrap();
+            let mut page = TableBase2::from_reader_and_heap(&*bytes, &[]);
+            page.loaded_location = Some(p);
+            let incoming_len = page.serialized_len();
+
+            while !self.cache.is_empty() && self.txn.is_none() && self.cache_bytes + incoming_len > self.cache_byte_limit {
+                match self.cache_lru.pop_lru() {
+                    Some(victim) => self.unload_page(victim),
+                    None => break,
+                }
+            }
+
+            self.cache_bytes += incoming_len;
+            self.cache.insert(p, page);
+        }
+        self.cache_lru.touch(p);
+
+        if let Some(txn) = self.txn.as_mut() {
+            if let Some(page) = self.cache.get_mut(&p) {
+                txn.capture(p, || page.snapshot());
+            }
+        }
+
+        self.cache.get_mut(&p).unwrap()
+    }
+
+    // Zero-copy counterpart to `get_page_at`: memory-maps the page's bytes straight out of the
+    // file instead of copying them into a fresh `Vec`. Returns the page's `PageBytes` --
+    // a compressed page still has to be decoded into an owned buffer (there's nothing to map
+    // zero-copy-style once zstd/rle/snappy are involved), so only the uncompressed case actually
+    // comes back as a `PageBytes::Mapped`. Feeds `load_page_cached_mapped` above.
+    //
+    // Re-maps the whole file on every call rather than caching the mapping across calls -- fine
+    // for occasional one-off fetches, but a hot path calling this repeatedly would want to cache
+    // the `Mmap` and only remap once the file grows past it.
+    pub fn get_page_mapped(&self, position: u64) -> io::Result<PageBytes> {
+        let mut header = [0u8; Self::PAGEOVERHEAD as usize];
+        self.file.read_at(&mut header, position)?;
+        let tag = u16::from_le_bytes([header[0], header[1]]);
+        let len = u32::from_le_bytes([header[2], header[3], header[4], header[5]]) as usize;
+        let body_start = (position + Self::PAGEOVERHEAD) as usize;
+
+        match tag {
+            Self::WORKING_PAGE => {
+                let mmap = Arc::new(crate::mmap_storage::Mmap::open(&self.file)?);
+                if body_start + len > mmap.len() {
+                    return Err(io::Error::new(
+                        io::ErrorKind::UnexpectedEof,
+                        format!("get_page_mapped: page at {} claims {} bytes past end of mapped file", position, len),
+                    ));
+                }
+                Ok(PageBytes::Mapped(mmap, body_start..body_start + len))
+            }
+            Self::COMPRESSED_PAGE => {
+                let mut raw = vec![0u8; len];
+                self.file.read_at(&mut raw, body_start as u64)?;
+                Ok(PageBytes::Owned(Self::decompress_page_body(&raw)))
+            }
+            Self::DELETED_PAGE => Err(io::Error::new(
+                io::ErrorKind::Other,
+                format!("get_page_mapped: page at {} has been freed", position),
+            )),
+            _ => Err(io::Error::new(
+                io::ErrorKind::Other,
+                format!("get_page_mapped: bad page tag at {}", position),
+            )),
+        }
+    }
+}
+
 
 impl<W: Write + Seek + Read> PageSerializer<W> {
     pub fn add_page(&mut self, mut buf: Vec<u8>, ch: ChunkHeader) -> u64 {
@@ -325,32 +799,109 @@ impl<W: Write + Seek + Read> PageSerializer<W> {
             assert!(buf.len() < sz as usize);
             buf.resize((sz) as usize, 0);
         }
-        // Check for deleted pages
-        let new_pos = {
-            if self.constant_size.is_some() && !self.deleted.is_empty() {
-                let pos = self.deleted.pop().unwrap().0;
-                self.file.seek(SeekFrom::Start(pos)).unwrap()
-            } else {
-                self.file.seek(SeekFrom::End(0)).unwrap()
+
+        // Leftover free region too big to hand whole to this page: split off a reusable
+        // `DELETED_PAGE`-tagged stub at `(pos, len)` once the real page write lands.
+        let mut split_stub: Option<(u64, u64)> = None;
+        let mut tag = Self::WORKING_PAGE;
+        let new_pos;
+        let payload_len;
+
+        if self.constant_size.is_some() && !self.deleted.is_empty() {
+            new_pos = self.file.seek(SeekFrom::Start(self.deleted.pop().unwrap().0)).unwrap();
+            payload_len = self.constant_size.unwrap();
+        } else if self.constant_size.is_none() {
+            // Constant-size tables skip this entirely: their fixed-width padding would eat
+            // whatever space compression saved.
+            if self.codec != Codec::None {
+                let compressed = compressor::compress_body(self.codec, &buf);
+                let mut framed = Vec::with_capacity(5 + compressed.len());
+                framed.push(self.codec.to_u8());
+                framed.extend_from_slice(&(buf.len() as u32).to_le_bytes());
+                framed.extend_from_slice(&compressed);
+                buf = framed;
+                tag = Self::COMPRESSED_PAGE;
+            }
+            let needed = Self::PAGEOVERHEAD + buf.len() as u64;
+            match self.free_list.allocate(needed) {
+                Some((pos, region_len)) => {
+                    new_pos = pos;
+                    let remainder = region_len - needed;
+                    if remainder >= Self::PAGEOVERHEAD {
+                        // Big enough to stay reusable on its own: split it off instead of
+                        // padding this page out to the whole region.
+                        split_stub = Some((pos + needed, remainder - Self::PAGEOVERHEAD));
+                        payload_len = buf.len() as u64;
+                    } else {
+                        // Too small to ever hold a deleted-page header again: fold it into this
+                        // page as trailing padding rather than leak an unreachable sliver.
+                        buf.resize(buf.len() + remainder as usize, 0);
+                        payload_len = buf.len() as u64;
+                    }
+                }
+                None => {
+                    new_pos = self.file.seek(SeekFrom::End(0)).unwrap();
+                    payload_len = buf.len() as u64;
+                }
+            }
+        } else {
+            new_pos = self.file.seek(SeekFrom::End(0)).unwrap();
+            payload_len = buf.len() as u64;
+        }
+
+        // Reuse a pooled scratch buffer for the header-plus-payload assembly below instead of a
+        // fresh heap allocation on every flush; fall back to an ad-hoc `Vec` on the rare occasion
+        // every pooled buffer is already checked out (see `page_buffer_pool`'s field comment).
+        let mut pooled = self.page_buffer_pool.acquire();
+        let mut fallback = Vec::new();
+        let page_bytes: &mut Vec<u8> = match pooled.as_deref_mut() {
+            Some(v) => {
+                v.clear();
+                v
             }
+            None => &mut fallback,
         };
-        self.file
-            .write_all(&PageSerializer::<W>::WORKING_PAGE.to_le_bytes())
-            .unwrap();
-        self.file
-            .write_all(&(self.constant_size.unwrap_or(buf.len() as u64) as u32).to_le_bytes())
-            .unwrap();
-        self.file.write_all(&buf).unwrap();
+        page_bytes.reserve(2 + 4 + buf.len());
+        page_bytes.extend_from_slice(&tag.to_le_bytes());
+        page_bytes.extend_from_slice(&(payload_len as u32).to_le_bytes());
+        page_bytes.extend_from_slice(&buf);
+
+        // Log the alloc's intent (flushed, not fsynced) before writing the page itself, so a crash
+        // mid-write leaves a detectable, checksummed journal entry for `replay_journal`.
+        let (slot, record) = self.journal.begin(&mut self.file, WalOp::Alloc, new_pos, 0, page_bytes.as_slice());
+
+        self.file.seek(SeekFrom::Start(new_pos)).unwrap();
+        self.file.write_all(page_bytes.as_slice()).unwrap();
+
+        self.journal.commit(&mut self.file, slot, &record);
+
+        if let Some((stub_pos, stub_len)) = split_stub {
+            self.file.seek(SeekFrom::Start(stub_pos)).unwrap();
+            self.file.write_all(&Self::DELETED_PAGE.to_le_bytes()).unwrap();
+            self.file.write_all(&(stub_len as u32).to_le_bytes()).unwrap();
+            self.free_list.insert(stub_pos, stub_len + Self::PAGEOVERHEAD);
+        }
 
         self.previous_headers.push(new_pos, ch);
 
         new_pos
     }
 
-    pub fn get_page(&mut self, position: u64) -> LimitedReader<&'_ mut W> {
+    pub fn get_page(&mut self, position: u64) -> PageReader<'_, W> {
         Self::file_get_page(&mut self.file, position)
     }
 
+    // A forward-only cursor starting at the first page, for scans that want to inspect each
+    // page's `ChunkHeader` cheaply via `peek_next_page`/`skip_page` instead of loading every
+    // page up front.
+    pub fn page_cursor(&mut self) -> PageCursor<'_, W> {
+        PageCursor {
+            file: &mut self.file,
+            pos: Self::JOURNAL_BASE + JOURNAL_REGION_SIZE,
+            peeked: None,
+        }
+    }
+
 
 
     pub fn get_in_all(&self, ty: u64, r: Option<TypeData>) -> Vec<u64> {
@@ -369,11 +920,58 @@ impl<W: Write + Seek + Read> PageSerializer<W> {
             .get_in_one_it(ty, r.clone());
 
 
-        if r.is_some() {
-            candidate_pages.rev().take(1).map(|a| a.1.location).collect()
+        if let Some(r) = &r {
+            // The range check above can only narrow to the one page whose limits straddle `r` --
+            // it can't tell whether `r` is actually present. `pkey_bloom` can: it's already
+            // resident in memory (part of the cached `ChunkHeader`), so checking it here skips
+            // `load_page_cached`'s disk read entirely for a page that merely straddles `r` without
+            // containing it. A page with no stored filter (`m == 0`) always reports "might
+            // contain", so this never skips a page we'd otherwise have scanned.
+            let key = r.encode_memcmp();
+            candidate_pages
+                .rev()
+                .take(1)
+                .filter(|a| a.1.ch.pkey_bloom.might_contain(&key))
+                .map(|a| a.1.location)
+                .collect()
         } else {
             candidate_pages.map(|a| a.1.location).collect()
         }
         // candidate_pages.filter_map(move |x| filt(&r, x))
     }
+
+    // Like `get_in_all`, but for `<`/`>`/`BETWEEN` predicates: a range can span more than one
+    // page, so every page of `ty` whose key limits overlap `bounds` is returned (in page order),
+    // not just the single page an equality lookup would land on.
+    pub fn get_in_range(&self, ty: u64, bounds: (Bound<TypeData>, Bound<TypeData>)) -> Vec<u64> {
+        self.previous_headers
+            .get_in_one_it(ty, None)
+            .filter(|(_, chv)| chv.ch.limits.overlaps(&bounds))
+            .map(|(_, chv)| chv.location)
+            .collect()
+    }
+
+    // Like `get_in_range`, but for `Filter::Equals` on a non-primary-key column: every page's
+    // `ChunkHeader` (already in memory via `previous_headers`, same as `limits`) carries a
+    // per-column zone map, so a page can be skipped without loading/decoding its body whenever
+    // `val` provably falls outside that column's `[min, max]` for the page. A page with no zone
+    // map computed for `col` (empty `column_zonemaps`, or a chunk written before it existed) is
+    // always kept -- "no stats" must mean "don't skip", not "skip everything".
+    pub fn get_in_all_by_zonemap(&self, ty: u64, col: usize, val: &TypeData) -> Vec<u64> {
+        self.get_in_all_by_zonemap_range(ty, col, (Bound::Included(val.clone()), Bound::Included(val.clone())))
+    }
+
+    // Like `get_in_all_by_zonemap`, but for `<`/`>`/`BETWEEN` on a non-primary-key column instead
+    // of `Filter::Equals`: a page is skipped whenever its zone map for `col` provably can't
+    // overlap `bounds` at all, same "no stats means don't skip" rule as the point-lookup case.
+    pub fn get_in_all_by_zonemap_range(&self, ty: u64, col: usize, bounds: (Bound<TypeData>, Bound<TypeData>)) -> Vec<u64> {
+        self.previous_headers
+            .get_in_one_it(ty, None)
+            .filter(|(_, chv)| match chv.ch.column_zonemaps.get(col) {
+                Some(zonemap) => zonemap.overlaps(&bounds),
+                None => true,
+            })
+            .map(|(_, chv)| chv.location)
+            .collect()
+    }
 }
@@ -40,6 +40,19 @@ pub trait SuitableDataType:
         todo!()
     }
     fn resolve_item(&mut self, _heap: &[u8], _index: u8) {}
+
+    // Order-preserving byte encoding of the primary key, used to key merges (e.g. compaction's
+    // min-heap) on comparable byte strings instead of repeatedly calling `first`/`partial_cmp`.
+    fn memcmp_key(&self) -> [u8; 8] {
+        self.first().to_be_bytes()
+    }
+
+    // Raw comparison bytes for a generalized secondary index keyed on the same `index` used
+    // by `resolve_item`, or `None` if that column isn't indexable. Columns backed by the heap
+    // (e.g. strings) must be resolved before this is called.
+    fn index_key(&self, _index: u8) -> Option<Vec<u8>> {
+        None
+    }
 }
 
 impl SuitableDataType for DataType {
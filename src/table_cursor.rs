@@ -1,26 +1,47 @@
+use std::ops::Bound;
+
 use dynamic_tuple::{DynamicTuple, RWS, TupleBuilder};
 use ra_ops::RANodeIterator;
 use serializer::PageSerializer;
 use crate::type_data::TypeData;
 
+// Walks every page overlapping the queried range one at a time (`locations`, popped off as each
+// page is drained), rather than gathering all of them into one big `Vec` and sorting it --
+// each page is already internally sorted (`force_flush`ed after `sort_self`), so there's nothing
+// to re-sort within a page. Note this doesn't make the *query* streaming end-to-end: every caller
+// (`NamedTables::execute_select` and friends) still calls `.collect(ps)` on top of this cursor,
+// materializing the full matching set into memory before returning it, the same way every other
+// `QueryData`-producing path in this crate does. Making that outer layer genuinely streaming
+// would mean reworking `QueryData` itself, not just this cursor.
 pub struct TableCursor<'a> {
     locations: Vec<u64>,
     ty: &'a DynamicTuple,
     // current_tuples: Vec<TupleBuilder>,
     current_index: u64,
     end_index_exclusive: u64,
-    pkey: Option<TypeData>,
+    range: (Bound<TypeData>, Bound<TypeData>),
     load_columns: u64,
 }
 
 impl<'a> TableCursor<'a> {
-    pub fn new< W: RWS>(locations: Vec<u64>, ps: & mut PageSerializer<W>, ty: &'a DynamicTuple, pkey: Option<TypeData>, load_columns: u64) -> Self {
+    pub fn new<W: RWS>(locations: Vec<u64>, ps: &mut PageSerializer<W>, ty: &'a DynamicTuple, pkey: Option<TypeData>, load_columns: u64) -> Self {
+        let range = match pkey {
+            Some(pk) => (Bound::Included(pk.clone()), Bound::Included(pk)),
+            None => (Bound::Unbounded, Bound::Unbounded),
+        };
+        Self::new_range(locations, ps, ty, range, load_columns)
+    }
+
+    // Like `new`, but takes an arbitrary bound pair instead of a single equality key -- the
+    // entry point for `<`/`>`/`BETWEEN` filters, which scan a contiguous run of rows rather
+    // than a single matching one.
+    pub fn new_range<W: RWS>(locations: Vec<u64>, ps: &mut PageSerializer<W>, ty: &'a DynamicTuple, range: (Bound<TypeData>, Bound<TypeData>), load_columns: u64) -> Self {
         let mut se = Self {
             locations,
             ty,
             current_index: 0,
             end_index_exclusive: 0,
-            pkey,
+            range,
             load_columns,
         };
         if !se.locations.is_empty() {
@@ -31,11 +52,7 @@ impl<'a> TableCursor<'a> {
     fn reset_index_iterator<W: RWS>(&mut self, ps: &mut PageSerializer<W>) {
         // Reload the index iterator for the new table
         let table = ps.load_page_cached(*self.locations.last().unwrap());
-        let range = if let Some(pk) = &self.pkey {
-            table.get_ranges(pk..=pk)
-        } else {
-            0..table.len()
-        };
+        let range = table.get_ranges(self.range.clone());
         self.current_index = range.start;
         self.end_index_exclusive = range.end;
     }
@@ -49,7 +66,7 @@ impl<W: RWS> RANodeIterator<W> for TableCursor<'_> {
             let table = ps.load_page_cached(*location);
 
             let bytes = table.load_index(self.current_index as usize);
-            let tuple = self.ty.read_tuple(bytes, self.load_columns, table.heap().get_ref());
+            let tuple = self.ty.read_tuple_borrowed(bytes, self.load_columns, table.heap().get_ref());
 
             self.current_index += 1;
             Some(tuple)
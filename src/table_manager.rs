@@ -1,6 +1,4 @@
-// todo: compression, secondary indexes
-
-use std::collections::HashMap;
+use std::collections::{BTreeMap, HashMap};
 use std::fmt::{Debug, Formatter};
 use std::io::{Cursor, Read, Seek, Write};
 use std::marker::PhantomData;
@@ -10,7 +8,13 @@ use std::option::Option::None;
 use serializer::PageSerializer;
 use FromReader;
 
+use crate::bloom::BloomFilter;
+use crate::bytes_serializer::BytesSerialize;
+use crate::compressor::{self, Codec};
+use crate::range::Range;
 use crate::suitable_data_type::SuitableDataType;
+use crate::table_base2::TableType;
+use crate::type_data::TypeData;
 use crate::ChunkHeader;
 
 #[allow(unused)]
@@ -18,4 +22,344 @@ fn setup_logging() {
     env_logger::init();
 }
 
+// Every row is its own page, tagged `ty = DATA_TY` and keyed in the page serializer's
+// `ChunkHeaderIndex` by the row's primary key (see `SuitableDataType::first`).
+const DATA_TY: u64 = 0;
+// Secondary indices are persisted one-per-column in their own page, tagged by column so they
+// can be found again on reload without scanning the whole file.
+const INDEX_TY_BASE: u64 = 100;
+
+// A persisted mapping from a column's raw comparison bytes (see `SuitableDataType::index_key`)
+// to the sorted list of primary keys holding that value. Sorted posting lists let callers page
+// through matches in key order, not just test membership.
+#[derive(Debug, Default, Clone)]
+pub struct SecondaryIndex {
+    entries: BTreeMap<Vec<u8>, Vec<u64>>,
+}
+
+impl SecondaryIndex {
+    fn insert(&mut self, key: Vec<u8>, pkey: u64) {
+        let list = self.entries.entry(key).or_insert_with(Vec::new);
+        let pos = list.partition_point(|&id| id < pkey);
+        if list.get(pos) != Some(&pkey) {
+            list.insert(pos, pkey);
+        }
+    }
+
+    fn remove(&mut self, key: &[u8], pkey: u64) {
+        if let Some(list) = self.entries.get_mut(key) {
+            if let Ok(pos) = list.binary_search(&pkey) {
+                list.remove(pos);
+            }
+            if list.is_empty() {
+                self.entries.remove(key);
+            }
+        }
+    }
+
+    fn get(&self, key: &[u8]) -> &[u64] {
+        self.entries.get(key).map(Vec::as_slice).unwrap_or(&[])
+    }
+
+    fn range<R: RangeBounds<Vec<u8>>>(&self, range: R) -> Vec<u64> {
+        self.entries
+            .range(range)
+            .flat_map(|(_, ids)| ids.iter().copied())
+            .collect()
+    }
+}
+
+impl BytesSerialize for SecondaryIndex {
+    fn serialize_with_heap<W: Write, W1: Write + Seek>(&self, mut w: W, _heap: W1) {
+        w.write_all(&(self.entries.len() as u64).to_le_bytes()).unwrap();
+        for (key, ids) in &self.entries {
+            w.write_all(&(key.len() as u32).to_le_bytes()).unwrap();
+            w.write_all(key).unwrap();
+            w.write_all(&(ids.len() as u32).to_le_bytes()).unwrap();
+            for id in ids {
+                w.write_all(&id.to_le_bytes()).unwrap();
+            }
+        }
+    }
+}
+
+impl FromReader for SecondaryIndex {
+    fn from_reader_and_heap<R: Read>(mut r: R, _heap: &[u8]) -> Self {
+        let mut count_buf = [0u8; 8];
+        r.read_exact(&mut count_buf).unwrap();
+        let count = u64::from_le_bytes(count_buf);
+
+        let mut entries = BTreeMap::new();
+        for _ in 0..count {
+            let mut len_buf = [0u8; 4];
+            r.read_exact(&mut len_buf).unwrap();
+            let mut key = vec![0u8; u32::from_le_bytes(len_buf) as usize];
+            r.read_exact(&mut key).unwrap();
+
+            let mut ids_len_buf = [0u8; 4];
+            r.read_exact(&mut ids_len_buf).unwrap();
+            let ids_len = u32::from_le_bytes(ids_len_buf);
+            let mut ids = Vec::with_capacity(ids_len as usize);
+            for _ in 0..ids_len {
+                let mut id_buf = [0u8; 8];
+                r.read_exact(&mut id_buf).unwrap();
+                ids.push(u64::from_le_bytes(id_buf));
+            }
+            entries.insert(key, ids);
+        }
+        SecondaryIndex { entries }
+    }
+}
+
+// Owns a table's rows (one `T` per page, keyed by `T::first()`) plus whatever secondary
+// indices have been created on it via `create_index`. Indices are updated automatically in
+// `store_and_replace` and persisted/reloaded alongside the table's own pages.
+pub struct TableManager<T: SuitableDataType, W: Write + Read + Seek> {
+    serializer: PageSerializer<W>,
+    indices: HashMap<u8, SecondaryIndex>,
+    codec: Codec,
+    _marker: PhantomData<T>,
+}
+
+impl<T: SuitableDataType, W: Write + Read + Seek> Debug for TableManager<T, W> {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        f.debug_struct("TableManager")
+            .field("indices", &self.indices.keys().collect::<Vec<_>>())
+            .finish()
+    }
+}
+
+impl<T: SuitableDataType, W: Write + Read + Seek> TableManager<T, W> {
+    pub fn new(w: W) -> Self {
+        Self {
+            serializer: PageSerializer::create(w, None, None),
+            indices: HashMap::new(),
+            codec: Codec::None,
+            _marker: PhantomData,
+        }
+    }
+
+    pub fn read_from_file(w: W) -> Self {
+        Self::from_serializer(PageSerializer::smart_create(w, None))
+    }
+
+    // Wraps an already-constructed `PageSerializer` (e.g. one rebuilt from an archive via
+    // `PageSerializer::open_archive`) and backfills `indices` from its persisted pages.
+    pub fn from_serializer(serializer: PageSerializer<W>) -> Self {
+        let mut s = Self {
+            serializer,
+            indices: HashMap::new(),
+            codec: Codec::None,
+            _marker: PhantomData,
+        };
+        s.load_indices();
+        s
+    }
+
+    // Packs this table's pages into a single-entry archive that `open_archive` can reload.
+    pub fn export_archive<W2: Write + Seek>(&mut self, w: &mut W2) {
+        self.serializer.export_archive(w);
+    }
+
+    // Rebuilds a table (including its secondary indices) from an archive written by
+    // `export_archive`.
+    pub fn open_archive<R: Read + Seek>(target: W, archive: R) -> Self {
+        Self::from_serializer(PageSerializer::open_archive(target, archive, None))
+    }
+
+    // Sets the codec used to compress row pages written from now on. Existing pages keep
+    // whatever codec they were written with -- `read_doc` always decodes per-page from
+    // `ChunkHeader::codec`, so mixing codecs across a table's lifetime is safe.
+    pub fn set_codec(&mut self, codec: Codec) {
+        self.codec = codec;
+    }
 
+    // Resizes the page cache's byte budget (see `PageSerializer::load_page_cached`).
+    pub fn set_cache_byte_limit(&mut self, limit: usize) {
+        self.serializer.set_cache_byte_limit(limit);
+    }
+
+    fn load_indices(&mut self) {
+        for column in 0u8..=255 {
+            let ty = INDEX_TY_BASE + column as u64;
+            if let Some(&location) = self.serializer.get_in_all(ty, None).last() {
+                let page = self.serializer.get_page(location);
+                let index = SecondaryIndex::from_reader_and_heap(page, &[]);
+                self.indices.insert(column, index);
+            }
+        }
+    }
+
+    fn read_doc<R: Read>(mut r: R, mask: u8) -> T {
+        let ch = ChunkHeader::from_reader_and_heap(&mut r, &[]);
+        let data_size = ch.tot_len - ch.heap_size;
+
+        let on_disk_len = if ch.compressed_size > 0 {
+            ch.compressed_size
+        } else {
+            ch.tot_len
+        };
+        let mut body = vec![0u8; on_disk_len as usize];
+        r.read_exact(&mut body).unwrap();
+        if ch.compressed_size > 0 {
+            body = compressor::decompress_body(Codec::from_u8(ch.codec), &body);
+        }
+        let (data, heap) = body.split_at(data_size as usize);
+
+        let mut t = T::from_reader_and_heap(data, heap);
+        for index in 0..8 {
+            if mask & (1 << index) != 0 {
+                t.resolve_item(heap, index);
+            }
+        }
+        t
+    }
+
+    // Builds a one-row page: `[ChunkHeader][row bytes][heap bytes]` (compressed as a single
+    // blob when `self.codec != Codec::None`), mirroring the framing `TableBase2` uses for its
+    // multi-row pages.
+    fn write_doc(&mut self, ty: u64, pkey: TypeData, t: &T) -> u64 {
+        let mut data = Vec::new();
+        let mut heap: Cursor<Vec<u8>> = Cursor::default();
+        t.serialize_with_heap(&mut data, &mut heap);
+        let heap = heap.into_inner();
+
+        let tot_len = (data.len() + heap.len()) as u32;
+        let mut body = data;
+        body.extend_from_slice(&heap);
+
+        let compressed_size = if self.codec != Codec::None {
+            body = compressor::compress_body(self.codec, &body);
+            body.len() as u32
+        } else {
+            0
+        };
+
+        let ch = ChunkHeader {
+            ty,
+            tot_len,
+            type_size: tot_len - heap.len() as u32,
+            tuple_count: 1,
+            heap_size: heap.len() as u32,
+            limits: Range::new(Some(pkey.clone()), Some(pkey)),
+            compressed_size,
+            table_type: TableType::Data,
+            bloom: BloomFilter::empty(),
+            codec: self.codec.to_u8(),
+            pkey_bloom: BloomFilter::empty(),
+            restart_encoded: false,
+            key_delta_encoded: false,
+            column_zonemaps: Vec::new(),
+        };
+
+        let mut page: Cursor<Vec<u8>> = Cursor::default();
+        ch.serialize_with_heap(&mut page, Cursor::default());
+        page.write_all(&body).unwrap();
+
+        self.serializer.add_page(page.into_inner(), ch)
+    }
+
+    pub fn get_one(&mut self, pkey: u64, mask: u8) -> Option<T> {
+        let location = *self
+            .serializer
+            .get_in_all(DATA_TY, Some(TypeData::Int(pkey)))
+            .first()?;
+        let page = self.serializer.get_page(location);
+        Some(Self::read_doc(page, mask))
+    }
+
+    pub fn get_in_all(&mut self, pkey: Option<TypeData>, mask: u8) -> Vec<T> {
+        self.serializer
+            .get_in_all(DATA_TY, pkey)
+            .into_iter()
+            .map(|location| {
+                let page = self.serializer.get_page(location);
+                Self::read_doc(page, mask)
+            })
+            .collect()
+    }
+
+    pub fn store_and_replace(&mut self, t: T) {
+        let pkey = t.first();
+
+        if let Some(old) = self.get_one(pkey, u8::MAX) {
+            for (&column, index) in self.indices.iter_mut() {
+                if let Some(key) = old.index_key(column) {
+                    index.remove(&key, pkey);
+                }
+            }
+            self.serializer.free_page(DATA_TY, TypeData::Int(pkey));
+        }
+
+        for (&column, index) in self.indices.iter_mut() {
+            if let Some(key) = t.index_key(column) {
+                index.insert(key, pkey);
+            }
+        }
+
+        self.write_doc(DATA_TY, TypeData::Int(pkey), &t);
+    }
+
+    // Declares `column` indexed and backfills it from every row already stored.
+    pub fn create_index(&mut self, column: u8) {
+        if self.indices.contains_key(&column) {
+            return;
+        }
+        let mut index = SecondaryIndex::default();
+        for doc in self.get_in_all(None, u8::MAX) {
+            if let Some(key) = doc.index_key(column) {
+                index.insert(key, doc.first());
+            }
+        }
+        self.indices.insert(column, index);
+    }
+
+    pub fn get_by_index(&self, column: u8, value: &[u8]) -> Vec<u64> {
+        self.indices
+            .get(&column)
+            .map(|index| index.get(value).to_vec())
+            .unwrap_or_default()
+    }
+
+    pub fn range_by_index<R: RangeBounds<Vec<u8>>>(&self, column: u8, range: R) -> Vec<u64> {
+        self.indices
+            .get(&column)
+            .map(|index| index.range(range))
+            .unwrap_or_default()
+    }
+
+    pub fn serializer(&mut self) -> &mut PageSerializer<W> {
+        &mut self.serializer
+    }
+
+    pub fn force_flush(&mut self) {
+        for (&column, index) in &self.indices {
+            let ty = INDEX_TY_BASE + column as u64;
+            let mut buf: Cursor<Vec<u8>> = Cursor::default();
+            index.serialize_with_heap(&mut buf, Cursor::default());
+            let buf = buf.into_inner();
+
+            let ch = ChunkHeader {
+                ty,
+                tot_len: buf.len() as u32,
+                type_size: 0,
+                tuple_count: 0,
+                heap_size: 0,
+                limits: Range::new(
+                    Some(TypeData::Int(column as u64)),
+                    Some(TypeData::Int(column as u64)),
+                ),
+                compressed_size: 0,
+                table_type: TableType::Data,
+                bloom: BloomFilter::empty(),
+                codec: 0,
+                pkey_bloom: BloomFilter::empty(),
+                restart_encoded: false,
+                key_delta_encoded: false,
+                column_zonemaps: Vec::new(),
+            };
+            self.serializer.add_page(buf, ch);
+        }
+        self.serializer.flush();
+    }
+}
@@ -0,0 +1,298 @@
+// Human-readable text codec mirroring the packed `BytesSerialize`/`FromReader` path.
+// Useful for debugging dumps and as a stable interchange form that isn't tied to the
+// in-memory binary row layout.
+
+use std::fmt::Write as FmtWrite;
+
+use crate::db1_string::Db1String;
+use crate::range::Range;
+use crate::type_data::TypeData;
+
+const BASE64_ALPHABET: &[u8; 64] =
+    b"ABCDEFGHIJKLMNOPQRSTUVWXYZabcdefghijklmnopqrstuvwxyz0123456789+/";
+
+fn base64_encode(bytes: &[u8]) -> String {
+    let mut out = String::with_capacity((bytes.len() + 2) / 3 * 4);
+    for chunk in bytes.chunks(3) {
+        let b0 = chunk[0];
+        let b1 = *chunk.get(1).unwrap_or(&0);
+        let b2 = *chunk.get(2).unwrap_or(&0);
+        out.push(BASE64_ALPHABET[(b0 >> 2) as usize] as char);
+        out.push(BASE64_ALPHABET[(((b0 & 0x03) << 4) | (b1 >> 4)) as usize] as char);
+        out.push(if chunk.len() > 1 {
+            BASE64_ALPHABET[(((b1 & 0x0f) << 2) | (b2 >> 6)) as usize] as char
+        } else {
+            '='
+        });
+        out.push(if chunk.len() > 2 {
+            BASE64_ALPHABET[(b2 & 0x3f) as usize] as char
+        } else {
+            '='
+        });
+    }
+    out
+}
+
+fn base64_decode(s: &str) -> Vec<u8> {
+    fn val(c: u8) -> Option<u8> {
+        BASE64_ALPHABET.iter().position(|&b| b == c).map(|i| i as u8)
+    }
+    let mut out = Vec::with_capacity(s.len() / 4 * 3);
+    for chunk in s.as_bytes().chunks(4) {
+        let v0 = val(chunk[0]).unwrap();
+        let v1 = val(chunk[1]).unwrap();
+        out.push((v0 << 2) | (v1 >> 4));
+        if chunk.len() > 2 && chunk[2] != b'=' {
+            let v2 = val(chunk[2]).unwrap();
+            out.push((v1 << 4) | (v2 >> 2));
+            if chunk.len() > 3 && chunk[3] != b'=' {
+                let v3 = val(chunk[3]).unwrap();
+                out.push((v2 << 6) | v3);
+            }
+        }
+    }
+    out
+}
+
+// Quote and escape a string the way the rest of the crate escapes `Db1String` for Debug
+// output, but in a form that can be parsed back unambiguously.
+fn quote_escape(s: &str, out: &mut String) {
+    out.push('"');
+    for c in s.chars() {
+        match c {
+            '"' => out.push_str("\\\""),
+            '\\' => out.push_str("\\\\"),
+            '\n' => out.push_str("\\n"),
+            _ => out.push(c),
+        }
+    }
+    out.push('"');
+}
+
+fn unquote_escape(s: &str) -> String {
+    let inner = &s[1..s.len() - 1];
+    let mut out = String::with_capacity(inner.len());
+    let mut chars = inner.chars();
+    while let Some(c) = chars.next() {
+        if c == '\\' {
+            match chars.next().unwrap() {
+                '"' => out.push('"'),
+                '\\' => out.push('\\'),
+                'n' => out.push('\n'),
+                other => out.push(other),
+            }
+        } else {
+            out.push(c);
+        }
+    }
+    out
+}
+
+pub trait ToText {
+    fn to_text(&self, out: &mut String);
+}
+
+pub trait FromText: Sized {
+    // Consumes and returns the unparsed remainder, mirroring `FromReader`'s reader-advancing style.
+    fn from_text(s: &str) -> (Self, &str);
+}
+
+impl ToText for Db1String {
+    fn to_text(&self, out: &mut String) {
+        let buf = self.as_buffer();
+        match std::str::from_utf8(buf) {
+            Ok(s) => quote_escape(s, out),
+            Err(_) => {
+                out.push_str("b64:");
+                out.push_str(&base64_encode(buf));
+            }
+        }
+    }
+}
+
+impl FromText for Db1String {
+    fn from_text(s: &str) -> (Self, &str) {
+        let s = s.trim_start();
+        if let Some(rest) = s.strip_prefix("b64:") {
+            let end = rest.find(char::is_whitespace).unwrap_or(rest.len());
+            (Db1String::from(base64_decode(&rest[..end])), &rest[end..])
+        } else {
+            assert_eq!(&s[0..1], "\"");
+            let mut end = 1;
+            let bytes = s.as_bytes();
+            while bytes[end] != b'"' {
+                if bytes[end] == b'\\' {
+                    end += 1;
+                }
+                end += 1;
+            }
+            end += 1;
+            (Db1String::from(unquote_escape(&s[..end])), &s[end..])
+        }
+    }
+}
+
+impl ToText for TypeData {
+    fn to_text(&self, out: &mut String) {
+        match self {
+            TypeData::Null => out.push_str("null"),
+            TypeData::Int(i) => write!(out, "int:{}", i).unwrap(),
+            TypeData::String(s) => {
+                out.push_str("str:");
+                s.to_text(out);
+            }
+            TypeData::Symbol(id) => write!(out, "sym:{}", id).unwrap(),
+            TypeData::Float(f) => write!(out, "float:{}", f).unwrap(),
+            TypeData::Bool(b) => write!(out, "bool:{}", b).unwrap(),
+            TypeData::Bytes(s) => {
+                out.push_str("bytes:");
+                s.to_text(out);
+            }
+            TypeData::Uuid(id) => {
+                out.push_str("uuid:");
+                for b in id {
+                    write!(out, "{:02x}", b).unwrap();
+                }
+            }
+        }
+    }
+}
+
+impl FromText for TypeData {
+    fn from_text(s: &str) -> (Self, &str) {
+        let s = s.trim_start();
+        if let Some(rest) = s.strip_prefix("null") {
+            (TypeData::Null, rest)
+        } else if let Some(rest) = s.strip_prefix("int:") {
+            let end = rest.find(char::is_whitespace).unwrap_or(rest.len());
+            (TypeData::Int(rest[..end].parse().unwrap()), &rest[end..])
+        } else if let Some(rest) = s.strip_prefix("str:") {
+            let (value, rest) = Db1String::from_text(rest);
+            (TypeData::String(value), rest)
+        } else if let Some(rest) = s.strip_prefix("sym:") {
+            let end = rest.find(char::is_whitespace).unwrap_or(rest.len());
+            (TypeData::Symbol(rest[..end].parse().unwrap()), &rest[end..])
+        } else if let Some(rest) = s.strip_prefix("float:") {
+            let end = rest.find(char::is_whitespace).unwrap_or(rest.len());
+            (TypeData::Float(rest[..end].parse().unwrap()), &rest[end..])
+        } else if let Some(rest) = s.strip_prefix("bool:") {
+            let end = rest.find(char::is_whitespace).unwrap_or(rest.len());
+            (TypeData::Bool(rest[..end].parse().unwrap()), &rest[end..])
+        } else if let Some(rest) = s.strip_prefix("bytes:") {
+            let (value, rest) = Db1String::from_text(rest);
+            (TypeData::Bytes(value), rest)
+        } else if let Some(rest) = s.strip_prefix("uuid:") {
+            let end = rest.find(char::is_whitespace).unwrap_or(rest.len());
+            let hex = &rest[..end];
+            let mut id = [0u8; 16];
+            for i in 0..16 {
+                id[i] = u8::from_str_radix(&hex[i * 2..i * 2 + 2], 16).unwrap();
+            }
+            (TypeData::Uuid(id), &rest[end..])
+        } else {
+            panic!("Invalid TypeData text {:?}", s)
+        }
+    }
+}
+
+impl ToText for Range<TypeData> {
+    fn to_text(&self, out: &mut String) {
+        out.push_str("range(");
+        self.min.as_ref().unwrap().to_text(out);
+        out.push_str(", ");
+        self.max.as_ref().unwrap().to_text(out);
+        out.push(')');
+    }
+}
+
+impl FromText for Range<TypeData> {
+    fn from_text(s: &str) -> (Self, &str) {
+        let rest = s.trim_start().strip_prefix("range(").unwrap();
+        let (min, rest) = TypeData::from_text(rest);
+        let rest = rest.trim_start().strip_prefix(',').unwrap();
+        let (max, rest) = TypeData::from_text(rest);
+        let rest = rest.trim_start().strip_prefix(')').unwrap();
+        (Range::new(Some(min), Some(max)), rest)
+    }
+}
+
+// Renders a row of columns as a human-readable, line-oriented dump that can be parsed back
+// with `row_from_text` into an equal `Vec<TypeData>` -- the text-format counterpart of a
+// `TableBase2` page's binary row encoding, for debugging dumps and stable-interchange use
+// cases that shouldn't be tied to the in-memory row layout.
+pub fn row_to_text(row: &[TypeData]) -> String {
+    let mut out = String::new();
+    writeln!(out, "row length={}", row.len()).unwrap();
+    for field in row {
+        field.to_text(&mut out);
+        out.push('\n');
+    }
+    out
+}
+
+pub fn row_from_text(text: &str) -> Vec<TypeData> {
+    let mut lines = text.lines();
+    let header = lines.next().unwrap();
+    let length: usize = header.strip_prefix("row length=").unwrap().parse().unwrap();
+
+    let mut row = Vec::with_capacity(length);
+    for line in lines.take(length) {
+        let (value, rest) = TypeData::from_text(line);
+        assert!(rest.trim().is_empty());
+        row.push(value);
+    }
+    row
+}
+
+#[test]
+fn type_data_round_trips_every_variant() {
+    let values = vec![
+        TypeData::Null,
+        TypeData::Int(42),
+        TypeData::String(Db1String::from(b"hello world".to_vec())),
+        TypeData::Symbol(7),
+        TypeData::Float(3.5),
+        TypeData::Bool(true),
+        TypeData::Bytes(Db1String::from(vec![0u8, 1, 2, 255])),
+        TypeData::Uuid([0xab; 16]),
+    ];
+    for value in values {
+        let mut text = String::new();
+        value.to_text(&mut text);
+        let (parsed, rest) = TypeData::from_text(&text);
+        assert_eq!(parsed, value);
+        assert!(rest.trim().is_empty());
+    }
+}
+
+#[test]
+fn db1_string_falls_back_to_base64_for_non_utf8_bytes() {
+    let original = Db1String::from(vec![0xff, 0xfe, 0x00, 0x10]);
+    let mut text = String::new();
+    original.to_text(&mut text);
+    assert!(text.starts_with("b64:"));
+    let (parsed, _) = Db1String::from_text(&text);
+    assert_eq!(parsed.as_buffer(), original.as_buffer());
+}
+
+#[test]
+fn range_round_trips() {
+    let original = Range::new(Some(TypeData::Int(1)), Some(TypeData::Int(10)));
+    let mut text = String::new();
+    original.to_text(&mut text);
+    let (parsed, rest) = Range::from_text(&text);
+    assert_eq!(parsed, original);
+    assert!(rest.trim().is_empty());
+}
+
+#[test]
+fn row_round_trips() {
+    let row = vec![
+        TypeData::Int(1),
+        TypeData::String(Db1String::from(b"a quoted \"value\"".to_vec())),
+        TypeData::Null,
+    ];
+    let text = row_to_text(&row);
+    let parsed = row_from_text(&text);
+    assert_eq!(parsed, row);
+}
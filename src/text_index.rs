@@ -0,0 +1,152 @@
+// Inverted full-text index over a document's free-text columns (e.g.
+// `ImageDocument::description`), maintained incrementally and queryable with TF-IDF scoring.
+
+use std::collections::HashMap;
+use std::io::{Read, Seek, Write};
+
+use crate::bytes_serializer::{BytesSerialize, FromReader};
+
+const STOPWORDS: &[&str] = &[
+    "a", "an", "the", "and", "or", "of", "to", "in", "on", "is", "it", "this", "that", "for",
+];
+
+// Simplified Porter-style stemmer: strips the handful of common suffixes so that
+// "running"/"runs"/"ran"-style variants tend to collapse onto the same term. Not a full
+// Porter implementation, just enough to dedupe obvious inflections.
+pub fn stem(word: &str) -> String {
+    for suffix in ["ing", "edly", "ed", "es", "s"] {
+        if word.len() > suffix.len() + 2 && word.ends_with(suffix) {
+            return word[..word.len() - suffix.len()].to_string();
+        }
+    }
+    word.to_string()
+}
+
+pub fn normalize(text: &str) -> Vec<String> {
+    crate::bloom::tokenize(text)
+        .into_iter()
+        .filter(|t| !STOPWORDS.contains(&t.as_str()))
+        .map(|t| stem(&t))
+        .collect()
+}
+
+#[derive(Debug, Default, PartialEq)]
+pub struct TextIndex {
+    // term -> posting list of (id, term_frequency), kept sorted by id.
+    postings: HashMap<String, Vec<(u64, u32)>>,
+    doc_count: u64,
+}
+
+impl TextIndex {
+    pub fn store(&mut self, id: u64, text: &str) {
+        // Re-indexing an `id` that's already present (e.g. an UPDATE on the document this index
+        // is built over) must replace its old postings rather than append a second copy under
+        // each term -- otherwise `search` would double-count it and `doc_count` would drift from
+        // the actual number of distinct documents. Every posting list stays sorted by id (see the
+        // `partition_point`-based insert below), so finding and dropping `id`'s old entry is a
+        // binary search per term, same as the insert it precedes.
+        let was_present = self.postings.values_mut().fold(false, |found, list| {
+            match list.binary_search_by_key(&id, |(doc_id, _)| *doc_id) {
+                Ok(pos) => {
+                    list.remove(pos);
+                    true
+                }
+                Err(_) => found,
+            }
+        });
+        if !was_present {
+            self.doc_count += 1;
+        }
+
+        let mut term_freq: HashMap<String, u32> = HashMap::new();
+        for term in normalize(text) {
+            *term_freq.entry(term).or_insert(0) += 1;
+        }
+        for (term, tf) in term_freq {
+            let list = self.postings.entry(term).or_insert_with(Vec::new);
+            let pos = list.partition_point(|(doc_id, _)| *doc_id < id);
+            list.insert(pos, (id, tf));
+        }
+    }
+
+    fn doc_freq(&self, term: &str) -> usize {
+        self.postings.get(term).map_or(0, |l| l.len())
+    }
+
+    // score = sum over query terms of tf * ln(N / df), descending.
+    pub fn search(&self, query: &str) -> Vec<(u64, f64)> {
+        let mut scores: HashMap<u64, f64> = HashMap::new();
+        let n = self.doc_count.max(1) as f64;
+
+        for term in normalize(query) {
+            let df = self.doc_freq(&term);
+            if df == 0 {
+                continue;
+            }
+            let idf = (n / df as f64).ln();
+            if let Some(list) = self.postings.get(&term) {
+                for &(id, tf) in list {
+                    *scores.entry(id).or_insert(0.0) += tf as f64 * idf;
+                }
+            }
+        }
+
+        let mut ranked: Vec<(u64, f64)> = scores.into_iter().collect();
+        ranked.sort_by(|a, b| b.1.partial_cmp(&a.1).unwrap());
+        ranked
+    }
+}
+
+impl BytesSerialize for TextIndex {
+    fn serialize_with_heap<W: Write, W1: Write + Seek>(&self, mut w: W, _heap: W1) {
+        w.write_all(&self.doc_count.to_le_bytes()).unwrap();
+        w.write_all(&(self.postings.len() as u64).to_le_bytes()).unwrap();
+        for (term, postings) in &self.postings {
+            let term_bytes = term.as_bytes();
+            w.write_all(&(term_bytes.len() as u32).to_le_bytes()).unwrap();
+            w.write_all(term_bytes).unwrap();
+            w.write_all(&(postings.len() as u32).to_le_bytes()).unwrap();
+            for (id, tf) in postings {
+                w.write_all(&id.to_le_bytes()).unwrap();
+                w.write_all(&tf.to_le_bytes()).unwrap();
+            }
+        }
+    }
+}
+
+impl FromReader for TextIndex {
+    fn from_reader_and_heap<R: Read>(mut r: R, _heap: &[u8]) -> Self {
+        let mut doc_count = [0u8; 8];
+        r.read_exact(&mut doc_count).unwrap();
+        let doc_count = u64::from_le_bytes(doc_count);
+
+        let mut term_count = [0u8; 8];
+        r.read_exact(&mut term_count).unwrap();
+        let term_count = u64::from_le_bytes(term_count);
+
+        let mut postings = HashMap::new();
+        for _ in 0..term_count {
+            let mut len_buf = [0u8; 4];
+            r.read_exact(&mut len_buf).unwrap();
+            let mut term_bytes = vec![0u8; u32::from_le_bytes(len_buf) as usize];
+            r.read_exact(&mut term_bytes).unwrap();
+            let term = String::from_utf8(term_bytes).unwrap();
+
+            let mut posting_count_buf = [0u8; 4];
+            r.read_exact(&mut posting_count_buf).unwrap();
+            let posting_count = u32::from_le_bytes(posting_count_buf);
+
+            let mut list = Vec::with_capacity(posting_count as usize);
+            for _ in 0..posting_count {
+                let mut id_buf = [0u8; 8];
+                let mut tf_buf = [0u8; 4];
+                r.read_exact(&mut id_buf).unwrap();
+                r.read_exact(&mut tf_buf).unwrap();
+                list.push((u64::from_le_bytes(id_buf), u32::from_le_bytes(tf_buf)));
+            }
+            postings.insert(term, list);
+        }
+
+        TextIndex { postings, doc_count }
+    }
+}
@@ -0,0 +1,89 @@
+// In-memory undo log backing `PageSerializer`'s transaction API (`begin_transaction`,
+// `savepoint`, `rollback`, `rollback_to_savepoint`, `commit_transaction`), which `NamedTables`
+// exposes to callers. Pages are snapshotted as their on-disk byte representation (via
+// `TableBase2::snapshot`) rather than cloned in memory, since `TableBase2` doesn't implement
+// `Clone` and this format already round-trips through `FromReader`.
+
+use std::collections::HashSet;
+
+#[derive(Debug, Default)]
+pub struct Transaction {
+    undo_log: Vec<(u64, Vec<u8>)>,
+    // Locations already snapshotted since the most recent savepoint (or `begin`, if there's
+    // none yet) -- a page only needs one pre-image per segment, so repeated writes to the same
+    // page between savepoints don't pile up redundant copies of the same bytes.
+    captured_since_marker: HashSet<u64>,
+    savepoints: Vec<(String, usize)>,
+}
+
+impl Transaction {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    // Records `location`'s current bytes in the undo log, unless it's already been captured
+    // since the last savepoint -- `snapshot` is only called (and the page actually serialized)
+    // the first time a location is touched in a segment.
+    pub fn capture(&mut self, location: u64, snapshot: impl FnOnce() -> Vec<u8>) {
+        if self.captured_since_marker.insert(location) {
+            self.undo_log.push((location, snapshot()));
+        }
+    }
+
+    pub fn savepoint(&mut self, name: String) {
+        self.savepoints.push((name, self.undo_log.len()));
+        self.captured_since_marker.clear();
+    }
+
+    // Pops everything recorded after the named savepoint off the log, returning those entries
+    // (oldest first) for the caller to restore. If a location was captured more than once after
+    // the marker (it was touched again in a later segment), the entry closest to the marker is
+    // the state to restore to -- callers should keep the first occurrence per location and
+    // discard the rest.
+    pub fn rollback_to_savepoint(&mut self, name: &str) -> Vec<(u64, Vec<u8>)> {
+        let marker_index = self
+            .savepoints
+            .iter()
+            .rposition(|(n, _)| n == name)
+            .unwrap_or_else(|| panic!("No such savepoint: {}", name));
+        let undo_from = self.savepoints[marker_index].1;
+        self.savepoints.truncate(marker_index);
+        self.captured_since_marker.clear();
+        self.undo_log.split_off(undo_from)
+    }
+
+    // Discards the whole transaction, returning every recorded entry (oldest first) to restore.
+    pub fn rollback_all(&mut self) -> Vec<(u64, Vec<u8>)> {
+        self.savepoints.clear();
+        self.captured_since_marker.clear();
+        std::mem::take(&mut self.undo_log)
+    }
+}
+
+#[test]
+fn test_transaction_savepoint_scopes_are_independent() {
+    let mut txn = Transaction::new();
+    txn.capture(1, || vec![1]);
+    txn.savepoint("a".to_string());
+    txn.capture(1, || vec![2]); // new segment: location 1 gets a fresh pre-image
+    txn.capture(2, || vec![3]);
+
+    let restored = txn.rollback_to_savepoint("a");
+    assert_eq!(restored, vec![(1, vec![2]), (2, vec![3])]);
+}
+
+#[test]
+fn test_transaction_capture_is_one_time_per_segment() {
+    let mut txn = Transaction::new();
+    let mut calls = 0;
+    txn.capture(1, || {
+        calls += 1;
+        vec![1]
+    });
+    txn.capture(1, || {
+        calls += 1;
+        vec![9]
+    });
+    assert_eq!(calls, 1);
+    assert_eq!(txn.rollback_all(), vec![(1, vec![1])]);
+}
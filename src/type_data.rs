@@ -14,6 +14,11 @@ impl PartialOrd for TypeData {
         let result = match (self, other) {
             (TypeData::Int(x), TypeData::Int(y)) => x.partial_cmp(y),
             (TypeData::String(x), TypeData::String(y)) => x.partial_cmp(y),
+            (TypeData::Symbol(x), TypeData::Symbol(y)) => x.partial_cmp(y),
+            (TypeData::Float(x), TypeData::Float(y)) => x.partial_cmp(y),
+            (TypeData::Bool(x), TypeData::Bool(y)) => x.partial_cmp(y),
+            (TypeData::Bytes(x), TypeData::Bytes(y)) => x.partial_cmp(y),
+            (TypeData::Uuid(x), TypeData::Uuid(y)) => x.partial_cmp(y),
             (TypeData::Null, TypeData::Null) => Some(Ordering::Equal),
             (TypeData::Null, _other) => Some(Ordering::Less),
             (_self_, TypeData::Null) => Some(Ordering::Greater),
@@ -29,6 +34,16 @@ impl PartialOrd for TypeData {
 pub enum Type {
     Int = 1,
     String = 2,
+    // Dictionary-encoded string column: physically stored as a `Dictionary` symbol id
+    // (`TypeData::Symbol`) and resolved back to `TypeData::String` by `NamedTables` on read.
+    Dictionary = 3,
+    Float = 4,
+    Bool = 5,
+    // Arbitrary byte payload, stored the same way as `String` (heap-indirected via
+    // `Db1String`) but without the UTF-8 text interpretation.
+    Bytes = 6,
+    // 16-byte UUID, stored inline (no heap indirection needed -- its width is fixed).
+    Uuid = 7,
 }
 
 impl From<u64> for Type {
@@ -36,18 +51,38 @@ impl From<u64> for Type {
         match i {
             1 => Type::Int,
             2 => Type::String,
+            3 => Type::Dictionary,
+            4 => Type::Float,
+            5 => Type::Bool,
+            6 => Type::Bytes,
+            7 => Type::Uuid,
             _ => panic!(),
         }
     }
 }
 
-#[derive(Debug, Eq, Clone)]
+// `Eq` is implemented by hand rather than derived: `Float(f64)` would otherwise force `derive`
+// to require `f64: Eq`, which it isn't (NaN). `PartialEq` below never gives `Float`/`Float`
+// special NaN treatment, so treating it as total here is no worse than the rest of the crate's
+// `.unwrap()`-on-`partial_cmp` handling of floats.
+#[derive(Debug, Clone)]
 pub enum TypeData {
     Int(u64),
     String(Db1String),
+    // A `Dictionary` symbol id standing in for a `Type::Dictionary` column's string value.
+    // `NamedTables` interns/resolves these at the insert/select boundary -- nothing below that
+    // layer ever needs to see the resolved bytes.
+    Symbol(u32),
+    Float(f64),
+    Bool(bool),
+    // Arbitrary byte payload -- see `Type::Bytes`.
+    Bytes(Db1String),
+    Uuid([u8; 16]),
     Null,
 }
 
+impl Eq for TypeData {}
+
 impl Ord for TypeData {
     fn cmp(&self, other: &Self) -> Ordering {
         self.partial_cmp(other).unwrap()
@@ -59,23 +94,207 @@ impl PartialEq for TypeData {
         match (self, other) {
             (TypeData::Int(x), TypeData::Int(y)) => x.eq(y),
             (TypeData::String(x), TypeData::String(y)) => x.eq(y),
+            (TypeData::Symbol(x), TypeData::Symbol(y)) => x.eq(y),
+            (TypeData::Float(x), TypeData::Float(y)) => x.eq(y),
+            (TypeData::Bool(x), TypeData::Bool(y)) => x.eq(y),
+            (TypeData::Bytes(x), TypeData::Bytes(y)) => x.eq(y),
+            (TypeData::Uuid(x), TypeData::Uuid(y)) => x.eq(y),
             (TypeData::Null, TypeData::Null) => true,
             _ => false,
         }
     }
 }
 
+// Byte-stuffs `buf` (every `0x00` becomes `0x00 0xFF`) and terminates it with `0x00 0x01`, so a
+// shorter payload always sorts before a longer one sharing its prefix and no payload byte can be
+// confused with the terminator. Shared by the `String` and `Bytes` memcmp encodings.
+fn escape_memcmp_bytes(buf: &[u8], out: &mut Vec<u8>) {
+    for &b in buf {
+        if b == 0x00 {
+            out.push(0x00);
+            out.push(0xFF);
+        } else {
+            out.push(b);
+        }
+    }
+    out.push(0x00);
+    out.push(0x01);
+}
+
+// Inverse of `escape_memcmp_bytes`. `buf` starts right after the type tag; returns the
+// unescaped payload and the number of tagged bytes consumed (including the terminator).
+fn unescape_memcmp_bytes(buf: &[u8]) -> (Vec<u8>, usize) {
+    let mut unescaped = Vec::new();
+    let mut i = 0;
+    loop {
+        match (buf[i], buf.get(i + 1)) {
+            (0x00, Some(0x01)) => {
+                i += 2;
+                break;
+            }
+            (0x00, Some(0xFF)) => {
+                unescaped.push(0x00);
+                i += 2;
+            }
+            (b, _) => {
+                unescaped.push(b);
+                i += 1;
+            }
+        }
+    }
+    (unescaped, i)
+}
+
+// Flips a float's bits so that an unsigned big-endian memcmp of the result matches IEEE 754
+// total order: positives (sign bit 0) get their sign bit set so they sort above negatives, and
+// negatives (sign bit 1) get all bits flipped so a more-negative value (larger magnitude, bits
+// closer to all-1s before flipping) sorts lower.
+fn encode_memcmp_float(f: f64) -> u64 {
+    let bits = f.to_bits();
+    if bits & (1 << 63) != 0 {
+        !bits
+    } else {
+        bits | (1 << 63)
+    }
+}
+
+fn decode_memcmp_float(encoded: u64) -> f64 {
+    let bits = if encoded & (1 << 63) != 0 {
+        encoded & !(1 << 63)
+    } else {
+        !encoded
+    };
+    f64::from_bits(bits)
+}
+
 impl TypeData {
+    // Resolves a still-`Unresolved` payload against the page heap; a no-op for every other
+    // variant. Mirrors `Db1String::resolve_item`, which this just forwards to.
+    pub fn resolve_item(&mut self, heap: &[u8]) {
+        match self {
+            TypeData::String(s) | TypeData::Bytes(s) => s.resolve_item(heap),
+            _ => {}
+        }
+    }
+
     const INT_TYPE: u8 = 1;
     const STRING_TYPE: u8 = 2;
+    const SYMBOL_TYPE: u8 = 3;
+    const FLOAT_TYPE: u8 = 4;
+    const BOOL_TYPE: u8 = 5;
+    const BYTES_TYPE: u8 = 6;
+    const UUID_TYPE: u8 = 7;
     const NULL_TYPE: u8 = 0;
     fn get_type_code(&self) -> u8 {
         match self {
             TypeData::Int(_) => TypeData::INT_TYPE,
             TypeData::String(_) => TypeData::STRING_TYPE,
+            TypeData::Symbol(_) => TypeData::SYMBOL_TYPE,
+            TypeData::Float(_) => TypeData::FLOAT_TYPE,
+            TypeData::Bool(_) => TypeData::BOOL_TYPE,
+            TypeData::Bytes(_) => TypeData::BYTES_TYPE,
+            TypeData::Uuid(_) => TypeData::UUID_TYPE,
             TypeData::Null => TypeData::NULL_TYPE,
         }
     }
+
+    // Order-preserving (memcmp) tag: NULL sorts before every other value; values of different
+    // variants order by tag (Int, String, Symbol, Float, Bool, Bytes, Uuid).
+    // Keeping this distinct from `get_type_code` means the on-disk tag layout can change
+    // independently of the memcmp byte-key layout.
+    const MEMCMP_NULL_TAG: u8 = 0;
+    const MEMCMP_INT_TAG: u8 = 1;
+    const MEMCMP_STRING_TAG: u8 = 2;
+    const MEMCMP_SYMBOL_TAG: u8 = 3;
+    const MEMCMP_FLOAT_TAG: u8 = 4;
+    const MEMCMP_BOOL_TAG: u8 = 5;
+    const MEMCMP_BYTES_TAG: u8 = 6;
+    const MEMCMP_UUID_TAG: u8 = 7;
+
+    // Encode into bytes such that `a.encode_memcmp() < b.encode_memcmp()` (by raw byte
+    // comparison) iff `a < b`. Used to binary-search flushed, still-serialized key streams
+    // without decoding every tuple first.
+    pub fn encode_memcmp(&self) -> Vec<u8> {
+        match self {
+            TypeData::Null => vec![Self::MEMCMP_NULL_TAG],
+            TypeData::Int(i) => {
+                let mut out = Vec::with_capacity(1 + 8);
+                out.push(Self::MEMCMP_INT_TAG);
+                out.extend_from_slice(&i.to_be_bytes());
+                out
+            }
+            TypeData::String(s) => {
+                let buf = s.as_buffer();
+                let mut out = Vec::with_capacity(1 + buf.len() + 2);
+                out.push(Self::MEMCMP_STRING_TAG);
+                escape_memcmp_bytes(buf, &mut out);
+                out
+            }
+            TypeData::Symbol(id) => {
+                let mut out = Vec::with_capacity(1 + 4);
+                out.push(Self::MEMCMP_SYMBOL_TAG);
+                out.extend_from_slice(&id.to_be_bytes());
+                out
+            }
+            TypeData::Float(f) => {
+                let mut out = Vec::with_capacity(1 + 8);
+                out.push(Self::MEMCMP_FLOAT_TAG);
+                out.extend_from_slice(&encode_memcmp_float(*f).to_be_bytes());
+                out
+            }
+            TypeData::Bool(b) => vec![Self::MEMCMP_BOOL_TAG, *b as u8],
+            TypeData::Bytes(s) => {
+                let buf = s.as_buffer();
+                let mut out = Vec::with_capacity(1 + buf.len() + 2);
+                out.push(Self::MEMCMP_BYTES_TAG);
+                escape_memcmp_bytes(buf, &mut out);
+                out
+            }
+            TypeData::Uuid(id) => {
+                let mut out = Vec::with_capacity(1 + 16);
+                out.push(Self::MEMCMP_UUID_TAG);
+                out.extend_from_slice(id);
+                out
+            }
+        }
+    }
+
+    // Inverse of `encode_memcmp`. Returns the decoded value and the number of bytes consumed.
+    pub fn decode_memcmp(buf: &[u8]) -> (Self, usize) {
+        match buf[0] {
+            Self::MEMCMP_NULL_TAG => (TypeData::Null, 1),
+            Self::MEMCMP_INT_TAG => {
+                let mut int_bytes = [0u8; 8];
+                int_bytes.copy_from_slice(&buf[1..9]);
+                (TypeData::Int(u64::from_be_bytes(int_bytes)), 9)
+            }
+            Self::MEMCMP_STRING_TAG => {
+                let (unescaped, consumed) = unescape_memcmp_bytes(&buf[1..]);
+                (TypeData::String(Db1String::from(unescaped)), 1 + consumed)
+            }
+            Self::MEMCMP_SYMBOL_TAG => {
+                let mut id_bytes = [0u8; 4];
+                id_bytes.copy_from_slice(&buf[1..5]);
+                (TypeData::Symbol(u32::from_be_bytes(id_bytes)), 5)
+            }
+            Self::MEMCMP_FLOAT_TAG => {
+                let mut float_bytes = [0u8; 8];
+                float_bytes.copy_from_slice(&buf[1..9]);
+                (TypeData::Float(decode_memcmp_float(u64::from_be_bytes(float_bytes))), 9)
+            }
+            Self::MEMCMP_BOOL_TAG => (TypeData::Bool(buf[1] != 0), 2),
+            Self::MEMCMP_BYTES_TAG => {
+                let (unescaped, consumed) = unescape_memcmp_bytes(&buf[1..]);
+                (TypeData::Bytes(Db1String::from(unescaped)), 1 + consumed)
+            }
+            Self::MEMCMP_UUID_TAG => {
+                let mut id = [0u8; 16];
+                id.copy_from_slice(&buf[1..17]);
+                (TypeData::Uuid(id), 17)
+            }
+            t => panic!("Invalid memcmp type tag {}", t),
+        }
+    }
 }
 
 impl FromReader for TypeData {
@@ -92,6 +311,29 @@ impl FromReader for TypeData {
             TypeData::STRING_TYPE => {
                 TypeData::String(Db1String::from_reader_and_heap(&mut r, heap))
             }
+            TypeData::SYMBOL_TYPE => {
+                let mut id: u32 = 0;
+                r.read_exact(slice_from_type(&mut id)).unwrap();
+                TypeData::Symbol(id)
+            }
+            TypeData::FLOAT_TYPE => {
+                let mut f: f64 = 0.0;
+                r.read_exact(slice_from_type(&mut f)).unwrap();
+                TypeData::Float(f)
+            }
+            TypeData::BOOL_TYPE => {
+                let mut b: u8 = 0;
+                r.read_exact(slice_from_type(&mut b)).unwrap();
+                TypeData::Bool(b != 0)
+            }
+            TypeData::BYTES_TYPE => {
+                TypeData::Bytes(Db1String::from_reader_and_heap(&mut r, heap))
+            }
+            TypeData::UUID_TYPE => {
+                let mut id = [0u8; 16];
+                r.read_exact(&mut id).unwrap();
+                TypeData::Uuid(id)
+            }
             TypeData::NULL_TYPE => {
                 TypeData::Null
             }
@@ -106,6 +348,11 @@ impl BytesSerialize for TypeData {
         match self {
             TypeData::Int(i) => data.write_all(&i.to_le_bytes()).unwrap(),
             TypeData::String(s) => s.serialize_with_heap(&mut data, heap),
+            TypeData::Symbol(id) => data.write_all(&id.to_le_bytes()).unwrap(),
+            TypeData::Float(f) => data.write_all(&f.to_le_bytes()).unwrap(),
+            TypeData::Bool(b) => data.write_all(&[*b as u8]).unwrap(),
+            TypeData::Bytes(s) => s.serialize_with_heap(&mut data, heap),
+            TypeData::Uuid(id) => data.write_all(id).unwrap(),
             TypeData::Null => {}
         }
     }
@@ -0,0 +1,377 @@
+// Runtime-defined, multi-column tables built directly on `TypeData`, for callers who don't
+// want to compile a new `SuitableDataType` struct per document shape (c.f. `ImageDocument`).
+
+use std::ffi::CStr;
+use std::fs::File;
+use std::io::{Read, Seek, Write};
+use std::os::raw::c_char;
+
+use crate::bytes_serializer::{BytesSerialize, FromReader};
+use crate::db1_string::Db1String;
+use crate::gen_suitable_data_type_impls;
+use crate::suitable_data_type::SuitableDataType;
+use crate::table_manager::TableManager;
+use crate::type_data::{Type, TypeData};
+
+// One row of a runtime-defined table: a value per column in `schema`. Column 0 is always the
+// primary key and must hold a `TypeData::Int` (see `SuitableDataType::first`).
+#[derive(Clone, Debug, PartialEq)]
+pub struct TypedRow {
+    pub schema: Vec<Type>,
+    pub values: Vec<TypeData>,
+}
+
+impl TypedRow {
+    pub fn new(schema: Vec<Type>, values: Vec<TypeData>) -> Self {
+        assert_eq!(schema.len(), values.len(), "schema/value count mismatch");
+        TypedRow { schema, values }
+    }
+}
+
+gen_suitable_data_type_impls!(TypedRow);
+
+impl BytesSerialize for TypedRow {
+    fn serialize_with_heap<W: Write, W1: Write + Seek>(&self, mut data: W, mut heap: W1) {
+        data.write_all(&(self.schema.len() as u32).to_le_bytes()).unwrap();
+        for ty in &self.schema {
+            data.write_all(&[*ty as u8]).unwrap();
+        }
+        for value in &self.values {
+            value.serialize_with_heap(&mut data, &mut heap);
+        }
+    }
+}
+
+impl FromReader for TypedRow {
+    fn from_reader_and_heap<R: Read>(mut r: R, heap: &[u8]) -> Self {
+        let mut count_buf = [0u8; 4];
+        r.read_exact(&mut count_buf).unwrap();
+        let count = u32::from_le_bytes(count_buf) as usize;
+
+        let mut schema = Vec::with_capacity(count);
+        for _ in 0..count {
+            let mut ty_buf = [0u8; 1];
+            r.read_exact(&mut ty_buf).unwrap();
+            schema.push(Type::from(ty_buf[0] as u64));
+        }
+
+        let mut values = Vec::with_capacity(count);
+        for _ in 0..count {
+            values.push(TypeData::from_reader_and_heap(&mut r, heap));
+        }
+        TypedRow { schema, values }
+    }
+}
+
+impl SuitableDataType for TypedRow {
+    const REQUIRES_HEAP: bool = true;
+
+    fn first(&self) -> u64 {
+        match self.values.first() {
+            Some(TypeData::Int(i)) => *i,
+            other => panic!("TypedRow primary key (column 0) must be an Int, got {:?}", other),
+        }
+    }
+
+    fn resolve_item(&mut self, heap: &[u8], index: u8) {
+        if let Some(value) = self.values.get_mut(index as usize) {
+            value.resolve_item(heap);
+        }
+    }
+
+    // Secondary-index key for `index`, keyed on the same order-preserving byte encoding used
+    // for the memcmp-sorted primary key (see `TypeData::encode_memcmp`).
+    fn index_key(&self, index: u8) -> Option<Vec<u8>> {
+        self.values.get(index as usize).map(TypeData::encode_memcmp)
+    }
+}
+
+// A predicate evaluated against a single column of a `TypedRow` table. `Lt`/`Eq` defer to
+// `TypeData`'s own `PartialOrd`/`PartialEq`, so across mismatched variants `Lt` panics and `Eq`
+// is simply false, exactly like every other place in the crate that compares two `TypeData`s.
+#[derive(Debug, Clone)]
+pub enum Predicate {
+    Eq(TypeData),
+    Lt(TypeData),
+    In(Vec<TypeData>),
+    IsNull,
+}
+
+impl Predicate {
+    fn matches(&self, value: &TypeData) -> bool {
+        match self {
+            Predicate::Eq(v) => value == v,
+            Predicate::Lt(v) => value < v,
+            Predicate::In(values) => values.iter().any(|v| value == v),
+            Predicate::IsNull => matches!(value, TypeData::Null),
+        }
+    }
+}
+
+impl<W: Write + Read + Seek> TableManager<TypedRow, W> {
+    // Evaluates `predicate` against `column` across every row. When `column` is the primary
+    // key (0) and the predicate pins it to one or more exact values, this goes through
+    // `get_one`'s limits-pruned page lookup instead of a full scan; every other predicate
+    // falls back to scanning `get_in_all`.
+    pub fn query(&mut self, column: usize, predicate: &Predicate) -> Vec<TypedRow> {
+        if column == 0 {
+            match predicate {
+                Predicate::Eq(TypeData::Int(pkey)) => {
+                    return self.get_one(*pkey, u8::MAX).into_iter().collect();
+                }
+                Predicate::In(values) => {
+                    return values
+                        .iter()
+                        .filter_map(|v| match v {
+                            TypeData::Int(pkey) => self.get_one(*pkey, u8::MAX),
+                            _ => None,
+                        })
+                        .collect();
+                }
+                _ => {}
+            }
+        }
+
+        self.get_in_all(None, u8::MAX)
+            .into_iter()
+            .filter(|row| {
+                row.values
+                    .get(column)
+                    .map(|value| predicate.matches(value))
+                    .unwrap_or(false)
+            })
+            .collect()
+    }
+}
+
+// --- FFI -------------------------------------------------------------------------------
+
+// Schema descriptor for a typed table: `columns[0..len]` are `Type` codes (1=Int, 2=String),
+// column 0 is always the primary key.
+#[repr(C)]
+pub struct FFISchema {
+    pub columns: *const u8,
+    pub len: u64,
+}
+
+impl FFISchema {
+    unsafe fn to_vec(&self) -> Vec<Type> {
+        std::slice::from_raw_parts(self.columns, self.len as usize)
+            .iter()
+            .map(|&b| Type::from(b as u64))
+            .collect()
+    }
+}
+
+// One column's value, tagged like `TypeData`'s own on-disk type code (0=Null, 1=Int,
+// 2=String).
+#[repr(C)]
+pub struct FFITypeData {
+    pub tag: u8,
+    pub int_value: u64,
+    pub str_ptr: *const c_char,
+    pub str_len: u64,
+}
+
+impl FFITypeData {
+    unsafe fn to_type_data(&self) -> TypeData {
+        match self.tag {
+            0 => TypeData::Null,
+            1 => TypeData::Int(self.int_value),
+            2 => TypeData::String(Db1String::from((self.str_ptr, self.str_len))),
+            t => panic!("Invalid FFITypeData tag {}", t),
+        }
+    }
+
+    // `value` must already be resolved (no `Db1String::Unresolved` payloads) -- true for
+    // anything `TableManager::get_one`/`query` hands back, since those always resolve with
+    // `mask = u8::MAX`.
+    fn from_type_data(value: &TypeData) -> Self {
+        match value {
+            TypeData::Null => FFITypeData {
+                tag: 0,
+                int_value: 0,
+                str_ptr: std::ptr::null(),
+                str_len: 0,
+            },
+            TypeData::Int(i) => FFITypeData {
+                tag: 1,
+                int_value: *i,
+                str_ptr: std::ptr::null(),
+                str_len: 0,
+            },
+            TypeData::String(s) => {
+                let (ptr, len) = s.as_ptr_allow_unresolved();
+                FFITypeData {
+                    tag: 2,
+                    int_value: 0,
+                    str_ptr: ptr as *const c_char,
+                    str_len: len,
+                }
+            }
+            TypeData::Symbol(_) => panic!("dictionary symbols are not exposed over the C FFI boundary"),
+            TypeData::Float(_) => panic!("float columns are not yet exposed over the C FFI boundary"),
+            TypeData::Bool(_) => panic!("bool columns are not yet exposed over the C FFI boundary"),
+            TypeData::Bytes(_) => panic!("bytes columns are not yet exposed over the C FFI boundary"),
+            TypeData::Uuid(_) => panic!("uuid columns are not yet exposed over the C FFI boundary"),
+        }
+    }
+}
+
+// Owns a `TableManager<TypedRow, _>` plus the result buffer of the last query, so returned
+// `FFITypeData`s (which may point into a row's resolved string payloads) stay alive until the
+// next call. Mirrors `ImageDb`'s `output_buf`/`output_buf_ffi` pattern.
+pub struct TypedTableDb<Writer: Write + Seek + Read = File> {
+    manager: TableManager<TypedRow, Writer>,
+    output_buf: Vec<TypedRow>,
+}
+
+impl TypedTableDb<File> {
+    unsafe fn setup_pointer<'a>(db: *mut TypedTableDb<File>) -> &'a mut Self {
+        let reference = &mut *db;
+        reference.output_buf.clear();
+        reference
+    }
+}
+
+#[no_mangle]
+pub unsafe extern "C" fn typed_table_new(path: *const c_char) -> *mut TypedTableDb<File> {
+    let path = CStr::from_ptr(path).to_str().unwrap();
+    let file = File::options().write(true).append(true).read(true).open(path);
+    let manager = match file {
+        Ok(f) => TableManager::read_from_file(f),
+        Err(_) => TableManager::new(
+            File::options()
+                .write(true)
+                .truncate(true)
+                .read(true)
+                .create(true)
+                .open(path)
+                .unwrap(),
+        ),
+    };
+    Box::leak(Box::new(TypedTableDb {
+        manager,
+        output_buf: Vec::new(),
+    }))
+}
+
+#[no_mangle]
+pub unsafe extern "C" fn typed_table_drop(db: *mut TypedTableDb<File>) {
+    let _ = Box::from_raw(db);
+}
+
+#[no_mangle]
+pub unsafe extern "C" fn typed_table_create_index(db: *mut TypedTableDb<File>, column: u8) {
+    TypedTableDb::setup_pointer(db).manager.create_index(column);
+}
+
+#[no_mangle]
+pub unsafe extern "C" fn typed_table_store(
+    db: *mut TypedTableDb<File>,
+    schema: FFISchema,
+    values: *const FFITypeData,
+    values_len: u64,
+) {
+    let schema = schema.to_vec();
+    let values = std::slice::from_raw_parts(values, values_len as usize)
+        .iter()
+        .map(|v| v.to_type_data())
+        .collect();
+    TypedTableDb::setup_pointer(db)
+        .manager
+        .store_and_replace(TypedRow::new(schema, values));
+}
+
+#[no_mangle]
+pub unsafe extern "C" fn typed_table_persist(db: *mut TypedTableDb<File>) {
+    TypedTableDb::setup_pointer(db).manager.force_flush();
+}
+
+// Runs `predicate` against `column` and buffers the matching rows; returns the match count.
+// Fetch individual values back out with `typed_table_result_get`.
+#[no_mangle]
+pub unsafe extern "C" fn typed_table_query(
+    db: *mut TypedTableDb<File>,
+    column: u64,
+    predicate: FFIPredicate,
+) -> u64 {
+    let db = TypedTableDb::setup_pointer(db);
+    db.output_buf = db.manager.query(column as usize, &predicate.to_predicate());
+    db.output_buf.len() as u64
+}
+
+#[no_mangle]
+pub unsafe extern "C" fn typed_table_result_get(
+    db: *mut TypedTableDb<File>,
+    row: u64,
+    column: u64,
+) -> FFITypeData {
+    let db = &mut *db;
+    let value = &db.output_buf[row as usize].values[column as usize];
+    FFITypeData::from_type_data(value)
+}
+
+// Tag + payload for the predicate a C caller wants evaluated; `tag` is 0=Eq, 1=Lt, 2=IsNull
+// (`In` isn't exposed over FFI -- callers can just issue one `Eq` query per value).
+#[repr(C)]
+pub struct FFIPredicate {
+    pub tag: u8,
+    pub value: FFITypeData,
+}
+
+impl FFIPredicate {
+    unsafe fn to_predicate(&self) -> Predicate {
+        match self.tag {
+            0 => Predicate::Eq(self.value.to_type_data()),
+            1 => Predicate::Lt(self.value.to_type_data()),
+            2 => Predicate::IsNull,
+            t => panic!("Invalid FFIPredicate tag {}", t),
+        }
+    }
+}
+
+#[test]
+fn test_typed_row_roundtrip() {
+    use std::io::Cursor;
+
+    let mut table: TableManager<TypedRow, Cursor<Vec<u8>>> = TableManager::new(Cursor::default());
+    table.create_index(1);
+
+    let schema = vec![Type::Int, Type::String, Type::Int];
+    for i in 0..10u64 {
+        let row = TypedRow::new(
+            schema.clone(),
+            vec![
+                TypeData::Int(i),
+                TypeData::from(format!("row{}", i).as_str()),
+                if i % 2 == 0 { TypeData::Int(100) } else { TypeData::Null },
+            ],
+        );
+        table.store_and_replace(row);
+    }
+    table.force_flush();
+
+    let found = table.query(0, &Predicate::Eq(TypeData::Int(5)));
+    assert_eq!(found.len(), 1);
+    assert_eq!(found[0].values[0], TypeData::Int(5));
+    assert_eq!(found[0].values[1], TypeData::from("row5"));
+
+    let nulls = table.query(2, &Predicate::IsNull);
+    assert_eq!(nulls.len(), 5);
+
+    let small = table.query(0, &Predicate::Lt(TypeData::Int(3)));
+    assert_eq!(small.len(), 3);
+
+    let in_set = table.query(
+        0,
+        &Predicate::In(vec![TypeData::Int(1), TypeData::Int(7), TypeData::Int(99)]),
+    );
+    assert_eq!(in_set.len(), 2);
+}
+
+#[test]
+#[should_panic]
+fn test_typed_row_mixed_type_comparison_panics() {
+    let _ = TypeData::Int(1) < TypeData::from("x");
+}
@@ -1,38 +1,144 @@
 use dynamic_tuple::{DynamicTuple, RWS, TupleBuilder};
 use serializer::PageSerializer;
+use std::cell::RefCell;
 use std::io::{Read, Seek, Write};
 use std::collections::HashMap;
+use std::ops::Bound;
 use secondary_index::SecondaryIndices;
 use table_base2::{TableBase2, TableType};
+use crate::compressor::Codec;
 use crate::table_cursor::TableCursor;
 use crate::type_data::{Type, TypeData};
 
+// Generation bookkeeping backing `TypedTable::{mark_live, tombstone, is_live}`: every insert and
+// every delete gets its own strictly increasing generation number, so "is this primary key
+// currently live" is just "was its newest insert generation more recent than its newest tombstone
+// generation". Keyed by `TypeData::encode_memcmp()` rather than `TypeData` itself since `TypeData`
+// has no `Hash` impl and memcmp bytes are already a canonical, comparable encoding of a value.
+//
+// This lives entirely in memory and is never flushed to a chunk -- a delete only ever writes a
+// couple of map entries here, never touches the page the row physically lives on. That's the
+// whole point: it makes point deletes on the primary key O(1) instead of the full-table rewrite
+// `NamedTables::execute_delete` otherwise has to fall back to.
+//
+// Known sharp edge: liveness is tracked per key, not per physical row, and this table already
+// tolerates several physical rows sharing one primary key (see `duplicate_pkeys_works` in
+// table_base2.rs). So a DELETE on a key followed by a later INSERT of that same key makes the
+// whole key live again, including any stale rows the delete left in place. Fine for the common
+// case of keys that are never reused after being deleted; a real fix needs per-row (not per-key)
+// identity, which would mean threading a generation through the physical row format itself.
+// Also note these maps only grow -- there's no pruning/compaction of entries for keys that will
+// never be looked up again, which is an acceptable tradeoff for now given nothing else in this
+// in-memory layer (e.g. `Dictionary`) is bounded or compacted either.
+#[derive(Clone, Debug, Default)]
+pub(crate) struct Tombstones {
+    next_generation: u64,
+    insert_generation: HashMap<Vec<u8>, u64>,
+    tombstone_generation: HashMap<Vec<u8>, u64>,
+}
+
 #[derive(Clone, Debug)]
 pub struct TypedTable {
     pub(crate) ty: DynamicTuple,
     pub(crate) id_ty: u64,
     pub(crate) column_map: HashMap<String, u32>,
-    /* TODO(index-on-insert): run inserts through secondary indices */
     pub(crate) attached_indices: SecondaryIndices,
+    pub(crate) tombstones: RefCell<Tombstones>,
+    // Codec newly-created pages are sealed with (see `store_raw`'s brand-new-page branch).
+    // `TableBase2::split` already carries a page's existing codec over to both halves, so this
+    // only needs to be applied once, at a page's birth -- letting different tables pick
+    // different tradeoffs (e.g. `Lz4` for a hot table, `Zstd` for an archival one) even though
+    // they all flush through the same `PageSerializer`.
+    pub(crate) codec: Codec,
 }
 
 impl TypedTable {
+    // Sets the codec brand-new pages of this table are sealed with from now on. Existing pages
+    // keep whatever codec they were written with -- `ChunkHeader::codec` is read back per-page
+    // on decode, so mixing codecs across a table's lifetime is safe.
+    pub fn set_codec(&mut self, codec: Codec) {
+        self.codec = codec;
+    }
+
     pub fn get_in_all_iter<W: RWS>(&self, pkey: Option<TypeData>, load_columns: u64, ps: & mut PageSerializer<W>) -> TableCursor<'_> {
         let location_iter = ps.get_in_all(self.id_ty, pkey.clone());
         TableCursor::new(location_iter, ps, &self.ty, pkey, load_columns)
     }
 
+    // `<`/`>`/`BETWEEN` scan over the primary key: walks every page whose key range overlaps
+    // `bounds`, in page order, instead of the single page an equality lookup lands on.
+    pub fn get_in_all_range_iter<W: RWS>(&self, bounds: (Bound<TypeData>, Bound<TypeData>), load_columns: u64, ps: &mut PageSerializer<W>) -> TableCursor<'_> {
+        let location_iter = ps.get_in_range(self.id_ty, bounds.clone());
+        TableCursor::new_range(location_iter, ps, &self.ty, bounds, load_columns)
+    }
+
+    // Equality scan on a non-pkey, non-indexed column, pruned by each page's zone map instead
+    // of visiting every page unconditionally -- the "inefficient table scan" fallback in
+    // `NamedTables::execute_select` used to call `get_in_all_iter(None, ...)` here, which loads
+    // every page regardless of whether it could possibly match.
+    pub fn get_in_all_by_zonemap_iter<W: RWS>(&self, col: usize, val: &TypeData, load_columns: u64, ps: &mut PageSerializer<W>) -> TableCursor<'_> {
+        let location_iter = ps.get_in_all_by_zonemap(self.id_ty, col, val);
+        TableCursor::new_range(location_iter, ps, &self.ty, (Bound::Unbounded, Bound::Unbounded), load_columns)
+    }
+
+    // Like `get_in_all_by_zonemap_iter`, but for `<`/`>`/`BETWEEN` on a non-pkey, non-indexed
+    // column -- the "inefficient table scan" fallback in `NamedTables::execute_select` for range
+    // predicates used to call `get_in_all_iter(None, ...)` here too, loading every page regardless
+    // of whether its zone map could possibly overlap the queried range.
+    pub fn get_in_all_by_zonemap_range_iter<W: RWS>(&self, col: usize, bounds: (Bound<TypeData>, Bound<TypeData>), load_columns: u64, ps: &mut PageSerializer<W>) -> TableCursor<'_> {
+        let location_iter = ps.get_in_all_by_zonemap_range(self.id_ty, col, bounds);
+        TableCursor::new_range(location_iter, ps, &self.ty, (Bound::Unbounded, Bound::Unbounded), load_columns)
+    }
+
+    // Records `pkey` as live at a fresh generation -- called on every insert (including a
+    // tombstone's own re-insert on UPDATE), so a pkey that was previously deleted becomes
+    // visible again without anyone having to explicitly clear its tombstone.
+    fn mark_live(&self, pkey: &TypeData) {
+        let mut t = self.tombstones.borrow_mut();
+        t.next_generation += 1;
+        let gen = t.next_generation;
+        t.insert_generation.insert(pkey.encode_memcmp(), gen);
+    }
+
+    // Marks `pkey` deleted as of a fresh generation. Doesn't touch the page the row physically
+    // lives on -- `is_live` is what makes the deletion visible to readers.
+    pub(crate) fn tombstone(&self, pkey: &TypeData) {
+        let mut t = self.tombstones.borrow_mut();
+        t.next_generation += 1;
+        let gen = t.next_generation;
+        t.tombstone_generation.insert(pkey.encode_memcmp(), gen);
+    }
+
+    // A pkey with no tombstone at all is live (nothing's ever been deleted); otherwise it's
+    // live iff its most recent insert is newer than its most recent tombstone.
+    pub(crate) fn is_live(&self, pkey: &TypeData) -> bool {
+        let t = self.tombstones.borrow();
+        let key = pkey.encode_memcmp();
+        match t.tombstone_generation.get(&key) {
+            None => true,
+            Some(deleted_at) => t.insert_generation.get(&key).map_or(false, |inserted_at| inserted_at > deleted_at),
+        }
+    }
+
     pub(crate) fn store_raw(&self, t: TupleBuilder, ps: &mut PageSerializer<impl RWS>) {
         assert!(t.type_check(&self.ty));
         let max_page_len = ps.maximum_serialized_len();
         let pkey = t.first_v2().clone();
+        self.mark_live(&pkey);
+        self.attached_indices.store(ps, &t);
         let (_location, page) = match ps.get_in_all_insert(self.id_ty, pkey.clone()) {
             Some(location) => {
                 let page = ps.load_page_cached(location);
                 if !page.limits.overlaps(&(&pkey..=&pkey)) {
                     ps.previous_headers
-                        .update_limits(self.id_ty, location, pkey);
+                        .update_limits(self.id_ty, location, pkey.clone());
                 }
+                // `insert_tb` below widens the *page's own* `column_zonemaps` in memory, but the
+                // `ChunkHeader` cached in `previous_headers` (what `get_in_all_by_zonemap`
+                // actually queries before this page's next flush) is a separate copy -- keep it
+                // in sync the same way `update_limits` does for `limits` above.
+                ps.previous_headers
+                    .widen_column_zonemaps(self.id_ty, location, pkey, &t.fields);
 
                 // Have to load page again because of the damn borrow checker...
                 let page = ps.load_page_cached(location);
@@ -47,12 +153,22 @@ impl TypedTable {
                 };
 
                 let mut new_page = TableBase2::new(self.id_ty, self.ty.size() as usize, table_type);
+                new_page.set_codec(self.codec);
                 new_page.insert_tb(t);
+                // Populate the zone map before this page is ever flushed -- a brand-new page
+                // starts with none (it's opt-in), and this is the only place that both holds
+                // `self.ty` and sees the page before its first flush.
+                new_page.set_column_zonemaps(new_page.build_column_zonemaps(&self.ty));
                 let location = new_page.force_flush(ps);
                 (location, ps.load_page_cached(location))
             }
         };
 
+        // Reclaim any interior heap fragmentation before deciding whether this page still needs
+        // to split -- cheap to check (an early return unless `freed_bytes()` has actually grown),
+        // and it can only shrink `serialized_len()`, never grow it.
+        page.compact_if_fragmented(&self.ty);
+
         // If estimated flush size is >= 16000, then we should split page to avoid going over page size limit
         if page.serialized_len() >= max_page_len {
             let old_min_limits = page.limits.min.clone().unwrap();
@@ -60,8 +176,11 @@ impl TypedTable {
             if let Some(mut x) = newpage {
                 assert!(!x.limits.overlaps(&page.limits), "{:?} {:?}", &x.limits, &page.limits);
                 let page_limits = page.limits.clone();
+                let page_zonemaps = page.column_zonemaps().to_vec();
+                ps.previous_headers
+                    .reset_limits(self.id_ty, old_min_limits, page_limits.clone());
                 ps.previous_headers
-                    .reset_limits(self.id_ty, old_min_limits, page_limits);
+                    .reset_column_zonemaps(self.id_ty, page_limits.min.unwrap(), page_zonemaps);
                 x.force_flush(ps);
             }
         }
@@ -86,6 +205,8 @@ impl TypedTable {
                 .map(|(ind, a)| (a.into(), ind as u32))
                 .collect(),
             attached_indices: Default::default(),
+            tombstones: Default::default(),
+            codec: Codec::None,
         }
     }
 }
@@ -0,0 +1,241 @@
+// A small redo journal that makes `PageSerializer::add_page`/`free_page` crash-safe. Each
+// mutation is logged to a fixed-size, fixed-count ring of journal slots living in a reserved
+// region right after the file's `CHECK_SEQ` magic, flushed via `Write::flush` (no fsync /
+// `sync_all` is issued), then the real page bytes are mutated, then the slot is marked
+// committed. On reopen, `PageSerializer::replay_journal` walks the ring and either finishes
+// (rolls forward) or discards any entry that was logged but never committed, so a crash
+// between the journal write and the page mutation can never leave `previous_headers` out of
+// sync with what's actually on disk.
+//
+// The crate has no crc32/checksum crate vendored (see `bloom.rs`'s hand-rolled FNV-1a for the
+// same reason), so this uses a standard table-based CRC-32 (IEEE 802.3 polynomial) computed
+// at first use.
+
+use std::io::{Read, Seek, SeekFrom, Write};
+use std::sync::OnceLock;
+
+pub const JOURNAL_SLOTS: usize = 64;
+// op:u8, committed:u8, pad:u8x2, page_pos:u64, old_len:u32, payload_checksum:u32, record_crc:u32
+const SLOT_SIZE: u64 = 1 + 1 + 2 + 8 + 4 + 4 + 4;
+pub const JOURNAL_REGION_SIZE: u64 = JOURNAL_SLOTS as u64 * SLOT_SIZE;
+
+fn crc32_table() -> &'static [u32; 256] {
+    static TABLE: OnceLock<[u32; 256]> = OnceLock::new();
+    TABLE.get_or_init(|| {
+        let mut table = [0u32; 256];
+        for (i, entry) in table.iter_mut().enumerate() {
+            let mut c = i as u32;
+            for _ in 0..8 {
+                c = if c & 1 != 0 {
+                    0xEDB88320 ^ (c >> 1)
+                } else {
+                    c >> 1
+                };
+            }
+            *entry = c;
+        }
+        table
+    })
+}
+
+pub fn crc32(data: &[u8]) -> u32 {
+    let table = crc32_table();
+    let mut crc = 0xFFFFFFFFu32;
+    for &b in data {
+        let idx = ((crc ^ b as u32) & 0xFF) as usize;
+        crc = table[idx] ^ (crc >> 8);
+    }
+    crc ^ 0xFFFFFFFF
+}
+
+#[derive(Debug, Copy, Clone, PartialEq, Eq)]
+pub enum WalOp {
+    Alloc,
+    Free,
+    Write,
+}
+
+impl WalOp {
+    fn to_u8(self) -> u8 {
+        match self {
+            WalOp::Alloc => 1,
+            WalOp::Free => 2,
+            WalOp::Write => 3,
+        }
+    }
+
+    fn from_u8(b: u8) -> Self {
+        match b {
+            1 => WalOp::Alloc,
+            2 => WalOp::Free,
+            3 => WalOp::Write,
+            _ => panic!("invalid WAL op tag {}", b),
+        }
+    }
+}
+
+#[derive(Debug, Clone)]
+pub struct JournalRecord {
+    pub op: WalOp,
+    pub page_pos: u64,
+    pub old_len: u32,
+    pub payload_checksum: u32,
+    pub committed: bool,
+}
+
+impl JournalRecord {
+    fn crc_input(op: u8, committed: u8, page_pos: u64, old_len: u32, payload_checksum: u32) -> Vec<u8> {
+        let mut v = Vec::with_capacity(SLOT_SIZE as usize - 4);
+        v.push(op);
+        v.push(committed);
+        v.extend_from_slice(&[0u8; 2]);
+        v.extend_from_slice(&page_pos.to_le_bytes());
+        v.extend_from_slice(&old_len.to_le_bytes());
+        v.extend_from_slice(&payload_checksum.to_le_bytes());
+        v
+    }
+
+    fn encode(&self) -> [u8; SLOT_SIZE as usize] {
+        let mut buf = [0u8; SLOT_SIZE as usize];
+        let committed = self.committed as u8;
+        let head = Self::crc_input(self.op.to_u8(), committed, self.page_pos, self.old_len, self.payload_checksum);
+        let crc = crc32(&head);
+        buf[..head.len()].copy_from_slice(&head);
+        buf[head.len()..].copy_from_slice(&crc.to_le_bytes());
+        buf
+    }
+
+    // Returns `None` when the slot's own CRC doesn't match -- an empty (never-written) slot or
+    // a torn write to the journal region itself, both of which are simply ignored on replay.
+    fn decode(buf: &[u8]) -> Option<Self> {
+        let head = &buf[..SLOT_SIZE as usize - 4];
+        let mut crc_bytes = [0u8; 4];
+        crc_bytes.copy_from_slice(&buf[SLOT_SIZE as usize - 4..]);
+        let stored_crc = u32::from_le_bytes(crc_bytes);
+        if crc32(head) != stored_crc {
+            return None;
+        }
+
+        let op = head[0];
+        let committed = head[1] != 0;
+        let mut page_pos_bytes = [0u8; 8];
+        page_pos_bytes.copy_from_slice(&head[4..12]);
+        let mut old_len_bytes = [0u8; 4];
+        old_len_bytes.copy_from_slice(&head[12..16]);
+        let mut checksum_bytes = [0u8; 4];
+        checksum_bytes.copy_from_slice(&head[16..20]);
+
+        Some(JournalRecord {
+            op: WalOp::from_u8(op),
+            page_pos: u64::from_le_bytes(page_pos_bytes),
+            old_len: u32::from_le_bytes(old_len_bytes),
+            payload_checksum: u32::from_le_bytes(checksum_bytes),
+            committed,
+        })
+    }
+}
+
+// Thin helper over the reserved journal region of a `PageSerializer`'s file. Doesn't own the
+// file itself (the caller passes it in for every call) so it composes with `PageSerializer`'s
+// existing single-`W` design instead of requiring a second writer.
+#[derive(Debug)]
+pub struct Wal {
+    // Offset of the journal region within the file (right after `CHECK_SEQ`).
+    base: u64,
+    next_slot: usize,
+}
+
+impl Wal {
+    pub fn new(base: u64) -> Self {
+        Wal { base, next_slot: 0 }
+    }
+
+    fn slot_offset(&self, slot: usize) -> u64 {
+        self.base + slot as u64 * SLOT_SIZE
+    }
+
+    pub fn zero_region<W: Write + Seek>(w: &mut W, base: u64) {
+        w.seek(SeekFrom::Start(base)).unwrap();
+        w.write_all(&vec![0u8; JOURNAL_REGION_SIZE as usize]).unwrap();
+    }
+
+    // Logs the intent to perform `op` on the page at `page_pos`, flushes it (no fsync), and returns the
+    // slot index plus the logged record -- the caller passes both to `commit` once the real
+    // mutation is done.
+    pub fn begin<W: Write + Seek>(
+        &mut self,
+        w: &mut W,
+        op: WalOp,
+        page_pos: u64,
+        old_len: u32,
+        payload: &[u8],
+    ) -> (usize, JournalRecord) {
+        let slot = self.next_slot;
+        self.next_slot = (self.next_slot + 1) % JOURNAL_SLOTS;
+
+        let record = JournalRecord {
+            op,
+            page_pos,
+            old_len,
+            payload_checksum: crc32(payload),
+            committed: false,
+        };
+        w.seek(SeekFrom::Start(self.slot_offset(slot))).unwrap();
+        w.write_all(&record.encode()).unwrap();
+        w.flush().unwrap();
+        (slot, record)
+    }
+
+    pub fn commit<W: Write + Seek>(&self, w: &mut W, slot: usize, record: &JournalRecord) {
+        let mut committed = record.clone();
+        committed.committed = true;
+        w.seek(SeekFrom::Start(self.slot_offset(slot))).unwrap();
+        w.write_all(&committed.encode()).unwrap();
+        w.flush().unwrap();
+    }
+
+    // Reads every slot in the journal region, keeping only entries whose own CRC validates
+    // (catches a torn write to the journal region itself).
+    pub fn read_all<W: Read + Seek>(w: &mut W, base: u64) -> Vec<(usize, JournalRecord)> {
+        let mut out = Vec::new();
+        let mut buf = [0u8; SLOT_SIZE as usize];
+        for slot in 0..JOURNAL_SLOTS {
+            w.seek(SeekFrom::Start(base + slot as u64 * SLOT_SIZE)).unwrap();
+            if w.read_exact(&mut buf).is_err() {
+                break;
+            }
+            if let Some(record) = JournalRecord::decode(&buf) {
+                out.push((slot, record));
+            }
+        }
+        out
+    }
+}
+
+#[test]
+fn test_crc32_stable() {
+    assert_eq!(crc32(b""), 0);
+    assert_eq!(crc32(b"123456789"), 0xCBF43926);
+}
+
+#[test]
+fn test_journal_record_roundtrip() {
+    use std::io::Cursor;
+
+    let mut file = Cursor::new(vec![0u8; JOURNAL_REGION_SIZE as usize]);
+    let mut wal = Wal::new(0);
+    let (slot, _) = wal.begin(&mut file, WalOp::Alloc, 128, 0, b"hello");
+
+    let all = Wal::read_all(&mut file, 0);
+    let (found_slot, record) = all.into_iter().find(|(s, _)| *s == slot).unwrap();
+    assert_eq!(found_slot, slot);
+    assert!(!record.committed);
+    assert_eq!(record.op, WalOp::Alloc);
+    assert_eq!(record.page_pos, 128);
+    assert_eq!(record.payload_checksum, crc32(b"hello"));
+
+    wal.commit(&mut file, slot, &record);
+    let all = Wal::read_all(&mut file, 0);
+    let (_, record) = all.into_iter().find(|(s, _)| *s == slot).unwrap();
+    assert!(record.committed);
+}